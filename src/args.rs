@@ -0,0 +1,167 @@
+use crate::model::{
+  exp::sentence::{latin::LatinOptions, roman::InputScheme},
+  game::SPEED_RANGE,
+};
+use std::fmt::{Display, Formatter, Result};
+
+#[derive(Debug, PartialEq)]
+pub enum ArgsError {
+  MissingValue { name: &'static str },
+  InvalidSpeed { value: String },
+  UnknownArgument { arg: String },
+}
+
+impl Display for ArgsError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    use ArgsError::*;
+    match self {
+      MissingValue { name } => {
+        write!(f, "`{}` の後に値がありません。", name)
+      }
+      InvalidSpeed { value } => write!(
+        f,
+        "`speed` には {} から {} までの百分率を渡してください。(`{}` が渡されました)",
+        SPEED_RANGE.0 * 100.0,
+        SPEED_RANGE.1 * 100.0,
+        value
+      ),
+      UnknownArgument { arg } => {
+        write!(f, "`{}` は分からない引数です。", arg)
+      }
+    }
+  }
+}
+
+// コマンドラインで渡された設定。引数の順番は問わない
+#[derive(Debug, Default)]
+pub struct Args {
+  // `replay <ファイル>` でリプレイを再生する
+  pub replay: Option<String>,
+  // 同じ譜面の最も良い記録と並べて遊ぶ
+  pub ghost: bool,
+  // `azik` などの名前で入力方式を選ぶ
+  pub input_scheme: Option<InputScheme>,
+  // 点数の付け方の名前
+  pub scoring: Option<String>,
+  pub life: bool,
+  pub practice: bool,
+  // 英語の歌詞で大文字と小文字、記号を打ち分けるか
+  pub latin_options: LatinOptions,
+  // 文の末尾の「ん」を n 1 つで打ち終えてよいか
+  pub trailing_n: bool,
+  // `speed 75` のように百分率で渡す
+  pub speed: Option<f64>,
+}
+
+impl Args {
+  // scorings は選べる点数の付け方の名前
+  pub fn parse<I>(
+    args: I,
+    scorings: &[String],
+  ) -> std::result::Result<Self, ArgsError>
+  where
+    I: IntoIterator<Item = String>,
+  {
+    use ArgsError::*;
+    let mut parsed = Self::default();
+    let mut args = args.into_iter();
+    while let Some(arg) = args.next() {
+      match arg.as_str() {
+        "replay" => {
+          parsed.replay =
+            Some(args.next().ok_or(MissingValue { name: "replay" })?);
+        }
+        "speed" => {
+          let value =
+            args.next().ok_or(MissingValue { name: "speed" })?;
+          let speed = value
+            .parse::<f64>()
+            .ok()
+            .map(|percent| percent / 100.0)
+            .filter(|speed| {
+              (SPEED_RANGE.0..=SPEED_RANGE.1).contains(speed)
+            })
+            .ok_or(InvalidSpeed { value })?;
+          parsed.speed = Some(speed);
+        }
+        "life" => parsed.life = true,
+        "practice" => parsed.practice = true,
+        "ghost" => parsed.ghost = true,
+        "trailing_n" => parsed.trailing_n = true,
+        "case_sensitive" => {
+          parsed.latin_options.case_sensitive = true
+        }
+        "strict_punctuation" => {
+          parsed.latin_options.strict_punctuation = true
+        }
+        name => {
+          if let Some(scheme) = InputScheme::from_name(name) {
+            parsed.input_scheme = Some(scheme);
+          } else if scorings.iter().any(|scoring| scoring == name) {
+            parsed.scoring = Some(arg);
+          } else {
+            return Err(UnknownArgument { arg });
+          }
+        }
+      }
+    }
+    Ok(parsed)
+  }
+}
+
+#[test]
+fn parse_args() {
+  use ArgsError::*;
+  let parse = |args: &str| {
+    Args::parse(
+      args.split_whitespace().map(String::from),
+      &["hard".into()],
+    )
+  };
+
+  let args =
+    parse("trailing_n ghost speed 75 azik replay last.tsv").unwrap();
+  assert_eq!(args.speed, Some(0.75));
+  assert_eq!(args.replay.as_deref(), Some("last.tsv"));
+  assert!(args.ghost && !args.life && !args.practice);
+  assert!(parse("life").unwrap().life);
+  assert!(parse("practice").unwrap().practice);
+  assert_eq!(
+    args.input_scheme.map(|scheme| scheme.name()),
+    Some("azik")
+  );
+  assert!(args.trailing_n);
+  assert_eq!(args.latin_options, LatinOptions::default());
+
+  let args = parse("strict_punctuation kana case_sensitive").unwrap();
+  assert_eq!(
+    args.input_scheme.map(|scheme| scheme.name()),
+    Some("kana")
+  );
+  assert!(args.latin_options.case_sensitive);
+  assert!(args.latin_options.strict_punctuation);
+  assert!(!args.trailing_n);
+  assert_eq!(args.replay, None);
+  assert!(!args.ghost);
+  assert_eq!(args.speed, None);
+  assert_eq!(args.scoring, None);
+  assert_eq!(parse("hard").unwrap().scoring.as_deref(), Some("hard"));
+
+  assert_eq!(
+    parse("kana replay").unwrap_err(),
+    MissingValue { name: "replay" }
+  );
+  for speed in ["fast", "200"] {
+    assert_eq!(
+      parse(&format!("speed {}", speed)).unwrap_err(),
+      InvalidSpeed {
+        value: speed.into()
+      }
+    );
+  }
+
+  assert_eq!(
+    parse("easy").unwrap_err(),
+    UnknownArgument { arg: "easy".into() }
+  );
+}
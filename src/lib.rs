@@ -0,0 +1,2 @@
+// 画面や音を使わない、譜面とゲームの進行。ボットやサーバーからも使える
+pub mod model;
@@ -1,14 +1,24 @@
-mod model;
+mod args;
 mod view;
 
-use model::exp::scoremap::ScoremapError;
-use std::{fs::File, path::Path};
+use args::Args;
+use musical_typer_rust::model;
+
+use model::{
+  exp::scoremap::ScoremapError,
+  game::{
+    score_hash, MusicalTyperConfig, MusicalTyperError, Replay,
+    ReplayError, ScoringProfile,
+  },
+};
+use std::{fs::read_to_string, path::Path};
 use view::ViewError;
 
 #[derive(Debug)]
 pub enum EntireError {
   ScoremapError(ScoremapError),
   ViewError(ViewError),
+  ReplayError(ReplayError),
 }
 
 impl From<ScoremapError> for EntireError {
@@ -17,6 +27,12 @@ impl From<ScoremapError> for EntireError {
   }
 }
 
+impl From<ReplayError> for EntireError {
+  fn from(err: ReplayError) -> Self {
+    EntireError::ReplayError(err)
+  }
+}
+
 impl From<ViewError> for EntireError {
   fn from(err: ViewError) -> Self {
     EntireError::ViewError(err)
@@ -24,12 +40,121 @@ impl From<ViewError> for EntireError {
 }
 
 fn main() -> Result<(), EntireError> {
-  use model::exp::scoremap::Scoremap;
-  let score = Scoremap::from_file(
-    File::open(Path::new("score/sampleScore.tsc")).unwrap(),
-    |config| config.ignore_unsupported_property(true),
-  )?;
+  use model::exp::{
+    game_activity::LifeRules, scoremap::Scoremap,
+    sentence::roman::RomanTable,
+  };
+  // 記録と同じ場所にローマ字表があれば同梱のものの代わりに使う
+  let table_path = view::data_dir().join("romaji.tsv");
+  if let Ok(table) = read_to_string(&table_path) {
+    match RomanTable::parse(&table) {
+      Ok(table) => {
+        if table.install().is_err() {
+          eprintln!(
+            "{}: ローマ字表は既に読み込まれています。",
+            table_path.display()
+          );
+        }
+      }
+      Err(err) => eprintln!("{}: {}", table_path.display(), err),
+    }
+  }
+  let source =
+    read_to_string(Path::new("score/sampleScore.tsc")).unwrap();
+  let (score, diagnostics) = Scoremap::load(&source, |config| config);
+  for diagnostic in diagnostics {
+    eprintln!("{}", diagnostic);
+  }
+  let score = match score {
+    Some(score) => score,
+    None => std::process::exit(1),
+  };
+
+  // 点数の付け方の名前を渡すとそれを使う。記録と同じ場所の scoring.tsv にあればそちらも選べる
+  let mut profiles = ScoringProfile::bundled();
+  let scoring_path = view::data_dir().join("scoring.tsv");
+  if let Ok(source) = read_to_string(&scoring_path) {
+    match ScoringProfile::parse_all(&source) {
+      Ok(local) => {
+        // 同じ名前なら手元のもので置き換える
+        for local in local {
+          match profiles.iter_mut().find(|p| p.name == local.name) {
+            Some(profile) => *profile = local,
+            None => profiles.push(local),
+          }
+        }
+      }
+      Err(err) => eprintln!("{}: {}", scoring_path.display(), err),
+    }
+  }
+
+  let names: Vec<String> = profiles
+    .iter()
+    .map(|profile| profile.name.clone())
+    .collect();
+  let args = match Args::parse(std::env::args().skip(1), &names) {
+    Ok(args) => args,
+    Err(err) => {
+      eprintln!("{}", err);
+      std::process::exit(2);
+    }
+  };
+  let replay = match &args.replay {
+    Some(path) => {
+      let replay = match read_to_string(Path::new(path)) {
+        Ok(replay) => Replay::parse(&replay)?,
+        Err(err) => {
+          eprintln!("{}: {}", path, err);
+          std::process::exit(1);
+        }
+      };
+      replay.check_score(&source)?;
+      replay.check_table()?;
+      Some(replay)
+    }
+    None => None,
+  };
+  // 指定がなければ最初のもの (standard)
+  let scoring = args
+    .scoring
+    .as_ref()
+    .and_then(|name| profiles.iter().find(|p| &p.name == name))
+    .unwrap_or(&profiles[0])
+    .clone();
 
-  view::run_router(score)?;
+  let mut config = MusicalTyperConfig::default()
+    .input_scheme(args.input_scheme.unwrap_or_default())
+    .latin_options(args.latin_options)
+    .trailing_n(args.trailing_n)
+    .scoring(scoring);
+  // ライフゲージを使い、尽きたら途中で終わる
+  if args.life {
+    config = config.life_gauge(LifeRules::default());
+  }
+  // 一時停止や移動、区間の繰り返しができる
+  if args.practice {
+    config = config.practice(true);
+  }
+  if let Some(speed) = args.speed {
+    config = config.speed(speed);
+  }
+
+  let result = view::run_router(
+    score,
+    score_hash(&source),
+    config,
+    replay,
+    args.ghost,
+  );
+  // 入力方式に合わせて読み直した譜面の問題は、読み込み時と同じく行を示す
+  if let Err(ViewError::ModelError(
+    MusicalTyperError::ScoremapBuildError(err),
+  )) = &result
+  {
+    let lines: Vec<String> = source.lines().map(Into::into).collect();
+    eprintln!("{}", err.diagnose(&lines));
+    std::process::exit(1);
+  }
+  result?;
   Ok(())
 }
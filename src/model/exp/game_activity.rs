@@ -1,14 +1,23 @@
 use super::{
-  note::{sentence::Sentence, Note, NoteContent, NoteId, TypeResult},
+  note::{
+    sentence::Sentence, Note, NoteContent, NoteId, NoteLog,
+    TypeResult,
+  },
   scoremap::sections::Sections,
   section::Section,
   time::Seconds,
 };
 
+mod life_gauge;
+
+pub use life_gauge::*;
+
 enum State {
   BeforeStart,
   OnGame,
   GameOver,
+  // ライフゲージが尽きて途中で終わった
+  Failed,
 }
 
 #[readonly::make]
@@ -17,6 +26,15 @@ pub struct GameScore {
   pub score_point: i32,
   pub achievement_rate: f64,
   pub accuracy: f64,
+  // 途切れずに正しく打ち続けたキーの数の最大
+  pub max_combo: u32,
+  // 譜面の終わりまでのうち、遊び終えたときに達していた割合
+  pub progress: f64,
+  // ライフゲージが尽きて途中で終わったか
+  pub failed: bool,
+  // 曲を流した速さ。1 以外なら通常の結果と区別する
+  pub speed: f64,
+  combo: u32,
   correction_type_count: u32,
   wrong_type_count: u32,
   all_roman_len: usize,
@@ -32,19 +50,36 @@ impl GameScore {
       score_point,
       achievement_rate,
       accuracy,
+      max_combo: 0,
+      progress: 0.0,
+      failed: false,
+      speed: 1.0,
+      combo: 0,
       correction_type_count: 0,
       wrong_type_count: 0,
       all_roman_len: 0,
     }
   }
 
+  pub fn correct_type_count(&self) -> u32 {
+    self.correction_type_count
+  }
+
+  // 今途切れずに正しく打ち続けているキーの数
+  pub fn combo(&self) -> u32 {
+    self.combo
+  }
+
   fn update(&mut self, type_result: &TypeResult) {
     match type_result {
       TypeResult::Mistaken => {
         self.wrong_type_count += 1;
+        self.combo = 0;
       }
       TypeResult::Succeed => {
         self.correction_type_count += 1;
+        self.combo += 1;
+        self.max_combo = self.max_combo.max(self.combo);
       }
       _ => return,
     };
@@ -64,7 +99,14 @@ impl GameScore {
 pub struct GameActivity {
   state: State,
   sections: Sections,
+  // 打つ前の譜面。戻ったときにノーツを打ち直せるようにする
+  initial: Sections,
   score: GameScore,
+  // 譜面の最後のノーツが終わる時間
+  end_time: Seconds,
+  // 最後に進めた時間。打鍵はこの時点で打ったものとする
+  now: Seconds,
+  life_gauge: Option<LifeGauge>,
 }
 
 impl GameActivity {
@@ -77,17 +119,31 @@ impl GameActivity {
         _ => acc,
       }) + acc
     });
+    let end_time = sections
+      .iter()
+      .filter_map(|section| section.iter().last())
+      .last()
+      .map_or(0.0.into(), |note| note.duration().end());
     let mut res = GameActivity {
       state: State::BeforeStart,
+      initial: sections.clone(),
       sections,
       score: GameScore {
         score_point: 0,
         achievement_rate: 0.0,
         accuracy: 0.0,
+        max_combo: 0,
+        progress: 0.0,
+        failed: false,
+        speed: 1.0,
+        combo: 0,
         correction_type_count: 0,
         wrong_type_count: 0,
         all_roman_len,
       },
+      end_time,
+      now: 0.0.into(),
+      life_gauge: None,
     };
     res.update_time(0.0.into());
     res
@@ -97,6 +153,10 @@ impl GameActivity {
     self.sections.current_section()
   }
 
+  pub fn current_section_index(&self) -> usize {
+    self.sections.current_section_index()
+  }
+
   pub fn current_note(&self) -> Option<&Note> {
     self
       .sections
@@ -104,7 +164,38 @@ impl GameActivity {
       .map(|section| section.current_note())
   }
 
+  pub fn set_speed(&mut self, speed: f64) {
+    self.score.speed = speed;
+  }
+
+  pub fn enable_life_gauge(&mut self, rules: LifeRules) {
+    self.life_gauge = Some(LifeGauge::new(rules));
+  }
+
+  pub fn life_gauge(&self) -> Option<&LifeGauge> {
+    self.life_gauge.as_ref()
+  }
+
+  // ライフゲージがあれば増減し、尽きたらそこで終える
+  pub fn change_life(&mut self, change: LifeChange) {
+    if let Some(gauge) = self.life_gauge.as_mut() {
+      gauge.change(change);
+      if gauge.is_empty() && !self.is_failed() {
+        self.state = State::Failed;
+        self.score.failed = true;
+      }
+    }
+  }
+
   pub fn update_time(&mut self, time: Seconds) {
+    if self.is_failed() {
+      return;
+    }
+    self.now = time;
+    if 0.0 < self.end_time.as_f64() {
+      self.score.progress =
+        (time.as_f64() / self.end_time.as_f64()).clamp(0.0, 1.0);
+    }
     self.state = State::OnGame;
     if self.sections.update(time).is_some() {
       return;
@@ -112,14 +203,51 @@ impl GameActivity {
     self.state = State::GameOver;
   }
 
+  // time に移る。そこから先のノーツは打つ前に戻す
+  pub fn seek(&mut self, time: Seconds) {
+    self.sections.reset_after(time, &self.initial);
+    self.update_time(time);
+  }
+
+  // 全てのノーツの、節と文の位置と始まる時間
+  pub fn note_starts(&self) -> Vec<((usize, usize), Seconds)> {
+    self
+      .sections
+      .iter()
+      .enumerate()
+      .flat_map(|(section_index, section)| {
+        section.iter().enumerate().map(move |(note_index, note)| {
+          ((section_index, note_index), note.duration().start())
+        })
+      })
+      .collect()
+  }
+
+  // 文のノーツそれぞれをどう打ったかの記録。遊び終えた後に結果と合わせて見る
+  pub fn note_logs(&self) -> Vec<NoteLog> {
+    self
+      .sections
+      .iter()
+      .enumerate()
+      .flat_map(|(index, section)| section.logs(index, self.now))
+      .collect()
+  }
+
+  pub fn sections(&self) -> impl Iterator<Item = &Section> {
+    self.sections.iter()
+  }
+
   pub fn input(&mut self, typed: char) -> TypeResult {
     use TypeResult::*;
     let res = if let State::OnGame = self.state {
-      self.sections.input(typed)
+      self.sections.input(typed, self.now)
     } else {
       Vacant
     };
     self.score.update(&res);
+    if let Mistaken = res {
+      self.change_life(LifeChange::WrongType);
+    }
     res
   }
 
@@ -132,6 +260,16 @@ impl GameActivity {
     Sentence::empty()
   }
 
+  // 譜面の中での、今の節と文の位置
+  pub fn current_position(&self) -> (usize, usize) {
+    (
+      self.current_section_index(),
+      self
+        .current_section()
+        .map_or(0, |section| section.current_note_index()),
+    )
+  }
+
   pub fn current_note_id(&self) -> NoteId {
     self.current_section().map_or("".into(), |s| s.id())
   }
@@ -152,6 +290,10 @@ impl GameActivity {
   }
 
   pub fn is_game_over(&self) -> bool {
-    matches!(self.state, State::GameOver)
+    matches!(self.state, State::GameOver | State::Failed)
+  }
+
+  pub fn is_failed(&self) -> bool {
+    matches!(self.state, State::Failed)
   }
 }
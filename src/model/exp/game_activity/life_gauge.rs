@@ -0,0 +1,100 @@
+// ライフゲージの増減の原因
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LifeChange {
+  WrongType,
+  MissedSentence,
+  CompletedSentence,
+  PerfectSentence,
+}
+
+// ライフゲージの最大値と、原因ごとの増減の量
+#[derive(Debug, Clone, PartialEq)]
+pub struct LifeRules {
+  pub max: f64,
+  pub wrong_type: f64,
+  pub missed_sentence: f64,
+  pub complete_sentence: f64,
+  pub perfect_sentence: f64,
+}
+
+impl Default for LifeRules {
+  fn default() -> Self {
+    Self {
+      max: 100.0,
+      wrong_type: 2.0,
+      missed_sentence: 10.0,
+      complete_sentence: 5.0,
+      perfect_sentence: 5.0,
+    }
+  }
+}
+
+impl LifeRules {
+  // 増やすなら正、減らすなら負
+  fn amount(&self, change: LifeChange) -> f64 {
+    match change {
+      LifeChange::WrongType => -self.wrong_type,
+      LifeChange::MissedSentence => -self.missed_sentence,
+      LifeChange::CompletedSentence => self.complete_sentence,
+      LifeChange::PerfectSentence => self.perfect_sentence,
+    }
+  }
+}
+
+// 満タンから始まり、0 になると失敗する
+#[derive(Debug, Clone, PartialEq)]
+pub struct LifeGauge {
+  rules: LifeRules,
+  life: f64,
+}
+
+impl LifeGauge {
+  pub fn new(rules: LifeRules) -> Self {
+    Self {
+      life: rules.max,
+      rules,
+    }
+  }
+
+  pub fn change(&mut self, change: LifeChange) {
+    self.life = (self.life + self.rules.amount(change))
+      .clamp(0.0, self.rules.max);
+  }
+
+  pub fn life(&self) -> f64 {
+    self.life
+  }
+
+  pub fn ratio(&self) -> f64 {
+    if self.rules.max <= 0.0 {
+      0.0
+    } else {
+      self.life / self.rules.max
+    }
+  }
+
+  pub fn is_empty(&self) -> bool {
+    self.life <= 0.0
+  }
+}
+
+#[test]
+fn drain_and_refill() {
+  let mut gauge = LifeGauge::new(LifeRules {
+    max: 10.0,
+    wrong_type: 3.0,
+    missed_sentence: 5.0,
+    complete_sentence: 4.0,
+    perfect_sentence: 2.0,
+  });
+  gauge.change(LifeChange::CompletedSentence);
+  assert_eq!(gauge.life(), 10.0);
+  gauge.change(LifeChange::WrongType);
+  gauge.change(LifeChange::MissedSentence);
+  assert_eq!(gauge.life(), 2.0);
+  gauge.change(LifeChange::PerfectSentence);
+  assert_eq!(gauge.ratio(), 0.4);
+  gauge.change(LifeChange::MissedSentence);
+  assert!(gauge.is_empty());
+  assert_eq!(gauge.life(), 0.0);
+}
@@ -1,52 +1,58 @@
 use sections::Sections;
-use std::{collections::HashMap, fs::File};
+use std::fs::File;
 
+pub mod diagnostic;
 pub mod lexer;
+pub mod metadata;
 pub mod parser;
 pub mod sections;
 mod tests;
 pub mod token;
+pub mod writer;
 
+use super::sentence::roman::RomanParseError;
+use diagnostic::Diagnostic;
 use lexer::{ScoremapLexError, ScoremapLoadConfig};
+pub use metadata::{MusicInfo, ScoremapMetadata};
 use parser::ScoremapParseError;
 
 #[derive(Debug)]
 pub enum ScoremapError {
   LexError(ScoremapLexError),
   ParseError(ScoremapParseError),
+  // 入力方式に合わせて読み直した読み仮名が打てない
+  InvalidYomigana {
+    line_num: usize,
+    err: RomanParseError,
+  },
+  // 譜面から読んでいない文の読み仮名が打てない
+  InvalidSentence(RomanParseError),
 }
 
-#[readonly::make]
-#[derive(PartialEq, Clone)]
-pub struct MusicInfo {
-  pub title: String,
-  pub song_author: String,
-}
-
-#[derive(Debug, Clone)]
-pub struct ScoremapMetadata(HashMap<String, String>);
-
-impl ScoremapMetadata {
-  pub fn new() -> Self {
-    Self(HashMap::new())
-  }
-
-  pub fn get(&self, key: &str) -> Option<&String> {
-    self.0.get(key)
+impl From<RomanParseError> for ScoremapError {
+  fn from(err: RomanParseError) -> Self {
+    ScoremapError::InvalidSentence(err)
   }
+}
 
-  pub fn get_music_info(&self) -> MusicInfo {
-    MusicInfo {
-      title: self
-        .0
-        .get("title")
-        .cloned()
-        .unwrap_or_else(|| "曲名不詳".into()),
-      song_author: self
-        .0
-        .get("song_author")
-        .cloned()
-        .unwrap_or_else(|| "作曲者不詳".into()),
+impl ScoremapError {
+  pub fn diagnose(&self, lines: &[String]) -> Diagnostic {
+    use ScoremapError::*;
+    match self {
+      LexError(err) => err.diagnose(lines),
+      ParseError(err) => err.diagnose(lines),
+      InvalidYomigana { line_num, err } => {
+        ScoremapLexError::InvalidYomigana {
+          line_num: *line_num,
+          err: err.clone(),
+        }
+        .diagnose(lines)
+      }
+      InvalidSentence(err) => ScoremapLexError::InvalidYomigana {
+        line_num: 0,
+        err: err.clone(),
+      }
+      .diagnose(lines),
     }
   }
 }
@@ -58,7 +64,6 @@ pub struct Scoremap {
 }
 
 impl Scoremap {
-  #[allow(dead_code)]
   pub fn from_str<C>(
     string: &str,
     configurator: C,
@@ -97,4 +102,61 @@ impl Scoremap {
 
     parser::parse(&tokens).map_err(ParseError)
   }
+
+  // 曲を speed 倍の速さで流したときの譜面。全ての時間を 1 / speed 倍にする
+  pub fn at_speed(mut self, speed: f64) -> Self {
+    let factor = 1.0 / speed;
+    self.sections.scale_time(factor);
+    self.metadata.scale_time(factor);
+    self
+  }
+
+  pub fn to_tsc(&self) -> String {
+    writer::write(self)
+  }
+
+  pub fn to_tsc_lossless(&self, original: &str) -> String {
+    writer::write_lossless(self, original)
+  }
+
+  pub fn diagnose<C>(string: &str, configurator: C) -> Vec<Diagnostic>
+  where
+    C: FnOnce(ScoremapLoadConfig) -> ScoremapLoadConfig,
+  {
+    Self::load(string, configurator).1
+  }
+
+  // 譜面を読み、見つかった問題を全て返す。エラーがなければ読んだ譜面も返す
+  pub fn load<C>(
+    string: &str,
+    configurator: C,
+  ) -> (Option<Self>, Vec<Diagnostic>)
+  where
+    C: FnOnce(ScoremapLoadConfig) -> ScoremapLoadConfig,
+  {
+    use std::io::BufReader;
+    let lines: Vec<String> = string.lines().map(Into::into).collect();
+    let reader = BufReader::new(string.as_bytes());
+    let (tokens, lex_errors, lex_warnings) = match lexer::lex_all(
+      configurator(lexer::ScoremapLoadConfig::new()),
+      reader,
+    ) {
+      Ok(lexed) => lexed,
+      Err(err) => return (None, vec![err.diagnose(&lines)]),
+    };
+    let (scoremap, parse_errors, parse_warnings) =
+      parser::parse_all(&tokens);
+    let scoremap = (lex_errors.is_empty() && parse_errors.is_empty())
+      .then_some(scoremap);
+
+    let mut diagnostics: Vec<_> = lex_errors
+      .iter()
+      .map(|err| err.diagnose(&lines))
+      .chain(parse_errors.iter().map(|err| err.diagnose(&lines)))
+      .chain(lex_warnings.iter().map(|warn| warn.diagnose(&lines)))
+      .chain(parse_warnings.iter().map(|warn| warn.diagnose(&lines)))
+      .collect();
+    diagnostics.sort_by_key(|diagnostic| diagnostic.span);
+    (scoremap, diagnostics)
+  }
 }
@@ -0,0 +1,361 @@
+use super::{
+  lexer::{
+    tempo::{BEATS_PER_BAR, TICKS_PER_BEAT},
+    ScoremapLexError, METADATA_KEYS,
+  },
+  metadata::EXTENSION_PREFIX,
+  parser::ScoremapParseError,
+};
+use crate::model::exp::sentence::roman::{
+  is_yomigana_char, RomanParseError,
+};
+use std::fmt::{Display, Formatter, Result};
+
+#[cfg(test)]
+mod tests;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Severity {
+  Error,
+  Warning,
+}
+
+impl Display for Severity {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    match self {
+      Severity::Error => write!(f, "エラー"),
+      Severity::Warning => write!(f, "警告"),
+    }
+  }
+}
+
+// 列は 1 から数えた文字単位で、end は含まない
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub struct Span {
+  pub line_num: usize,
+  pub start: usize,
+  pub end: usize,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+  pub severity: Severity,
+  pub span: Span,
+  pub snippet: String,
+  pub message: String,
+  pub suggestion: Option<String>,
+}
+
+impl Display for Diagnostic {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    write!(
+      f,
+      "{} {}:{}-{}: {}",
+      self.severity,
+      self.span.line_num,
+      self.span.start,
+      self.span.end,
+      self.message
+    )?;
+    if !self.snippet.is_empty() {
+      write!(f, " `{}`", self.snippet)?;
+    }
+    if let Some(suggestion) = &self.suggestion {
+      write!(f, "\n  ヒント: {}", suggestion)?;
+    }
+    Ok(())
+  }
+}
+
+impl Diagnostic {
+  fn on_line(
+    severity: Severity,
+    line_num: usize,
+    lines: &[String],
+    message: impl Into<String>,
+  ) -> Self {
+    let line = line_of(lines, line_num);
+    let leading =
+      line.chars().take_while(|c| c.is_whitespace()).count();
+    let trimmed = line.trim();
+    Self {
+      severity,
+      span: Span {
+        line_num,
+        start: leading + 1,
+        end: leading + 1 + trimmed.chars().count(),
+      },
+      snippet: trimmed.into(),
+      message: message.into(),
+      suggestion: None,
+    }
+  }
+
+  fn columns(
+    mut self,
+    lines: &[String],
+    start: usize,
+    end: usize,
+  ) -> Self {
+    let line = line_of(lines, self.span.line_num);
+    self.span.start = start;
+    self.span.end = end;
+    self.snippet = line
+      .chars()
+      .skip(start.saturating_sub(1))
+      .take(end.saturating_sub(start))
+      .collect();
+    self
+  }
+
+  fn suggest(mut self, suggestion: impl Into<String>) -> Self {
+    self.suggestion = Some(suggestion.into());
+    self
+  }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoremapWarning {
+  UnsupportedProperty { line_num: usize },
+  IgnoredTiming { line_num: usize },
+  DeprecatedCommand { line_num: usize },
+  LyricsLikeYomigana { line_num: usize },
+}
+
+fn line_of(lines: &[String], line_num: usize) -> &str {
+  line_num
+    .checked_sub(1)
+    .and_then(|index| lines.get(index))
+    .map_or("", |line| line.as_str())
+}
+
+fn property_key_columns(line: &str) -> (usize, usize) {
+  let key_len = line
+    .chars()
+    .skip(1)
+    .take_while(|c| !c.is_whitespace())
+    .count();
+  (2, 2 + key_len)
+}
+
+fn edit_distance(a: &str, b: &str) -> usize {
+  let b: Vec<char> = b.chars().collect();
+  let mut prev: Vec<usize> = (0..=b.len()).collect();
+  for (i, ca) in a.chars().enumerate() {
+    let mut curr = vec![i + 1];
+    for (j, cb) in b.iter().enumerate() {
+      let replaced = prev[j] + if ca == *cb { 0 } else { 1 };
+      curr.push(replaced.min(prev[j + 1] + 1).min(curr[j] + 1));
+    }
+    prev = curr;
+  }
+  prev[b.len()]
+}
+
+fn suggest_property(line: &str) -> String {
+  let key: String = line
+    .chars()
+    .skip(1)
+    .take_while(|c| !c.is_whitespace())
+    .collect();
+  METADATA_KEYS
+    .iter()
+    .map(|candidate| (edit_distance(&key, candidate), candidate))
+    .filter(|(distance, _)| *distance <= 2)
+    .min()
+    .map_or_else(
+      || {
+        format!(
+          "使用できるプロパティは {} です。独自のプロパティは `{}` で始めてください。",
+          METADATA_KEYS.join("、"),
+          EXTENSION_PREFIX
+        )
+      },
+      |(_, candidate)| {
+        format!("`:{}` の誤りではありませんか?", candidate)
+      },
+    )
+}
+
+impl ScoremapLexError {
+  pub fn diagnose(&self, lines: &[String]) -> Diagnostic {
+    use ScoremapLexError::*;
+    use Severity::*;
+    match self {
+      InternalRegexCompileFailure(err) => Diagnostic::on_line(
+        Error,
+        0,
+        lines,
+        format!("内部エラー: {}", err),
+      ),
+      CaptureFailure | UnknownToken => {
+        Diagnostic::on_line(Error, 0, lines, "内部エラーです。")
+      }
+      ParsingNumberFailure { line_num } => Diagnostic::on_line(
+        Error,
+        *line_num,
+        lines,
+        "数値を読み取れません。",
+      )
+      .suggest("`*秒` または `|分` の形式で書いてください。"),
+      UnexpectedEndOfFile { line_num } => Diagnostic::on_line(
+        Error,
+        *line_num,
+        lines,
+        "この行を読み込めません。",
+      )
+      .suggest("ファイルを UTF-8 で保存してください。"),
+      InvalidPropertyDefinition { line_num, reason } => {
+        let line = line_of(lines, *line_num);
+        let (start, end) = property_key_columns(line);
+        Diagnostic::on_line(Error, *line_num, lines, *reason)
+          .columns(lines, start, end)
+          .suggest(suggest_property(line))
+      }
+      InvalidPropertyValue { line_num, reason } => {
+        let line = line_of(lines, *line_num);
+        let (_, key_end) = property_key_columns(line);
+        let start = line
+          .chars()
+          .enumerate()
+          .skip(key_end - 1)
+          .find(|(_, c)| !c.is_whitespace())
+          .map_or(key_end, |(index, _)| index + 1);
+        let end = line.trim_end().chars().count() + 1;
+        let key: String = line
+          .chars()
+          .skip(1)
+          .take_while(|c| !c.is_whitespace())
+          .collect();
+        let example = match key.as_str() {
+          "bpm" => "`:bpm 120`",
+          "offset" => "`:offset 00.250`",
+          "song_data" => "`:song_data song.ogg`",
+          _ => "`:title 曲名`",
+        };
+        Diagnostic::on_line(Error, *line_num, lines, *reason)
+          .columns(lines, start, end)
+          .suggest(format!("{} のように書いてください。", example))
+      }
+      InvalidTempo { line_num, reason } => {
+        Diagnostic::on_line(Error, *line_num, lines, *reason)
+          .suggest("`[bpm 120]` のように書いてください。")
+      }
+      InvalidBeatTiming { line_num, reason } => Diagnostic::on_line(
+        Error,
+        *line_num,
+        lines,
+        *reason,
+      )
+      .suggest(format!(
+        "`*小節:拍:ティック` の形式で、拍は 1 から {}、ティックは 0 から {} までにしてください。",
+        BEATS_PER_BAR,
+        TICKS_PER_BEAT - 1
+      )),
+      InvalidYomigana { line_num, err } => {
+        let RomanParseError::IllegalYomigana { position, found } =
+          err;
+        let line = line_of(lines, *line_num);
+        let colon = line.chars().take_while(|c| *c != ':').count();
+        let start = colon + 2 + position;
+        Diagnostic::on_line(
+          Error,
+          *line_num,
+          lines,
+          "ふりがなでのそのような平仮名の並びは未対応です。",
+        )
+        .columns(lines, start, start + 1)
+        .suggest(format!(
+          "`{}` とその前後の平仮名を見直してください。",
+          found
+        ))
+      }
+    }
+  }
+}
+
+impl ScoremapParseError {
+  pub fn diagnose(&self, lines: &[String]) -> Diagnostic {
+    use ScoremapParseError::*;
+    use Severity::*;
+    match self {
+      InvalidCommand { line_num, reason } => {
+        Diagnostic::on_line(Error, *line_num, lines, *reason).suggest(
+          "[start] で歌詞定義を始め、[end] で終えてください。",
+        )
+      }
+      InvalidPropertyDefinition { line_num, reason } => {
+        Diagnostic::on_line(Error, *line_num, lines, *reason)
+          .suggest("プロパティは [start] より前に移動してください。")
+      }
+      InvalidStatementDefinition { line_num, reason } => {
+        Diagnostic::on_line(Error, *line_num, lines, *reason).suggest(
+          "歌詞、読み仮名、キャプションは [start] と [end] の間に、歌詞、読み仮名の順で書いてください。",
+        )
+      }
+      InvalidTimingDefinition { line_num, reason } => {
+        Diagnostic::on_line(Error, *line_num, lines, *reason)
+          .suggest("時間指定は [start] と [end] の間に移動してください。")
+      }
+      InvalidDuration { line_num, err } => Diagnostic::on_line(
+        Error,
+        *line_num,
+        lines,
+        format!("歌詞の出現時間が不正です。{}", err),
+      )
+      .suggest("時間指定が前の時間指定より後になるようにしてください。"),
+    }
+  }
+}
+
+impl ScoremapWarning {
+  pub fn diagnose(&self, lines: &[String]) -> Diagnostic {
+    use ScoremapWarning::*;
+    use Severity::*;
+    match self {
+      UnsupportedProperty { line_num } => {
+        let line = line_of(lines, *line_num);
+        let (start, end) = property_key_columns(line);
+        Diagnostic::on_line(
+          Warning,
+          *line_num,
+          lines,
+          "未対応のプロパティを無視しました。",
+        )
+        .columns(lines, start, end)
+        .suggest(suggest_property(line))
+      }
+      IgnoredTiming { line_num } => Diagnostic::on_line(
+        Warning,
+        *line_num,
+        lines,
+        "それ以前に遡る時間指定は無視されます。",
+      )
+      .suggest("時間指定は時間の順に並べてください。"),
+      DeprecatedCommand { line_num } => Diagnostic::on_line(
+        Warning,
+        *line_num,
+        lines,
+        "break コマンドは非推奨で、効果を持ちません。",
+      )
+      .suggest("この行を削除してください。"),
+      LyricsLikeYomigana { line_num } => {
+        let line = line_of(lines, *line_num);
+        let start = line
+          .chars()
+          .enumerate()
+          .skip(1)
+          .find(|(_, c)| !is_yomigana_char(*c))
+          .map_or(1, |(index, _)| index + 1);
+        Diagnostic::on_line(
+          Warning,
+          *line_num,
+          lines,
+          "読み仮名として読めないため、歌詞として扱います。",
+        )
+        .columns(lines, start, start + 1)
+        .suggest("読み仮名は仮名、英数字、句読点で書いてください。")
+      }
+    }
+  }
+}
@@ -0,0 +1,168 @@
+use super::{Diagnostic, Severity, Span};
+use crate::model::exp::scoremap::Scoremap;
+
+#[test]
+fn reports_every_problem() {
+  let diagnostics = Scoremap::diagnose(
+    r#"# Broken
+:title TEST
+:titel TEST
+:song_data void.ogg
+
+[start]
+*1.0
+>> 前奏
+[break]
+
+*2.0
+打鍵テスト
+:だけん★てすと
+:bpm 120
+
+*1.5
+*3.0
+[fin]
+[end]
+"#,
+    |config| config,
+  );
+
+  let summary: Vec<_> = diagnostics
+    .iter()
+    .map(|Diagnostic { severity, span, .. }| (*severity, *span))
+    .collect();
+  assert_eq!(
+    summary,
+    vec![
+      (
+        Severity::Error,
+        Span {
+          line_num: 3,
+          start: 2,
+          end: 7,
+        }
+      ),
+      (
+        Severity::Warning,
+        Span {
+          line_num: 9,
+          start: 1,
+          end: 8,
+        }
+      ),
+      (
+        Severity::Warning,
+        Span {
+          line_num: 13,
+          start: 5,
+          end: 6,
+        }
+      ),
+      (
+        Severity::Error,
+        Span {
+          line_num: 14,
+          start: 1,
+          end: 9,
+        }
+      ),
+      (
+        Severity::Warning,
+        Span {
+          line_num: 16,
+          start: 1,
+          end: 5,
+        }
+      ),
+      (
+        Severity::Error,
+        Span {
+          line_num: 18,
+          start: 1,
+          end: 6,
+        }
+      ),
+    ]
+  );
+
+  assert_eq!(diagnostics[0].snippet, "titel");
+  assert_eq!(
+    diagnostics[0].suggestion.as_deref(),
+    Some("`:title` の誤りではありませんか?")
+  );
+  assert_eq!(diagnostics[2].snippet, "★");
+  assert_eq!(diagnostics[5].snippet, "[fin]");
+}
+
+#[test]
+fn clean_score() {
+  let diagnostics = Scoremap::diagnose(
+    r#"
+:title TEST
+:song_data void.ogg
+
+[start]
+*2.22
+打鍵テスト
+:だけんてすと
+
+*3.0
+[end]
+"#,
+    |config| config,
+  );
+  assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+}
+
+#[test]
+fn display() {
+  let diagnostics = Scoremap::diagnose(":titel TEST\n", |config| {
+    config.ignore_unsupported_property(true)
+  });
+  assert_eq!(diagnostics.len(), 1);
+  assert_eq!(
+    diagnostics[0].to_string(),
+    "警告 1:2-7: 未対応のプロパティを無視しました。 `titel`\n  ヒント: `:title` の誤りではありませんか?"
+  );
+}
+
+#[test]
+fn yomigana_line() {
+  use super::super::ScoremapError;
+  use crate::model::exp::sentence::roman::RomanParseError;
+  let source = r#"
+:song_data void.ogg
+
+[start]
+*2.22
+打鍵テスト
+:だけんてすと
+
+*3.0
+[end]
+"#;
+  let (score, diagnostics) = Scoremap::load(source, |config| config);
+  assert!(diagnostics.is_empty(), "{:?}", diagnostics);
+  let mut sections = score.unwrap().sections;
+  let lines: Vec<_> = sections
+    .sentences_mut()
+    .map(|(line_num, _)| line_num)
+    .collect();
+  assert_eq!(lines, [7]);
+
+  // 入力方式に合わせて読み直したときの誤りも、その行で示す
+  let lines: Vec<String> = source.lines().map(Into::into).collect();
+  let diagnostic = ScoremapError::InvalidYomigana {
+    line_num: 7,
+    err: RomanParseError::IllegalYomigana {
+      position: 2,
+      found: 'ん',
+    },
+  }
+  .diagnose(&lines);
+  assert_eq!(
+    (diagnostic.span.line_num, diagnostic.span.start),
+    (7, 4)
+  );
+  assert_eq!(diagnostic.snippet, "ん");
+}
@@ -1,10 +1,14 @@
 use regex::Regex;
 use std::io::{BufReader, Read};
 
-use super::token::Token;
+use super::{diagnostic::ScoremapWarning, token::Token};
+use crate::model::exp::sentence::roman::RomanParseError;
 use pattern::{LexerCtx, TokenResult, Tokenizer};
 
-mod pattern;
+pub use pattern::METADATA_KEYS;
+
+pub(super) mod pattern;
+pub(super) mod tempo;
 #[cfg(test)]
 mod tests;
 
@@ -18,16 +22,32 @@ impl From<regex::Error> for ScoremapLexError {
 pub enum ScoremapLexError {
   InternalRegexCompileFailure(regex::Error),
   CaptureFailure,
-  ParsingNumberFailure,
-  UnexpectedEndOfFile,
+  ParsingNumberFailure {
+    line_num: usize,
+  },
+  UnexpectedEndOfFile {
+    line_num: usize,
+  },
   InvalidPropertyDefinition {
     line_num: usize,
     reason: &'static str,
   },
-  InvalidStatementDefinition {
+  InvalidPropertyValue {
+    line_num: usize,
+    reason: &'static str,
+  },
+  InvalidTempo {
+    line_num: usize,
+    reason: &'static str,
+  },
+  InvalidBeatTiming {
     line_num: usize,
     reason: &'static str,
   },
+  InvalidYomigana {
+    line_num: usize,
+    err: RomanParseError,
+  },
   UnknownToken,
 }
 
@@ -37,6 +57,7 @@ pub struct ScoremapLoadConfig {
 }
 
 impl ScoremapLoadConfig {
+  #[allow(clippy::new_without_default)]
   pub fn new() -> Self {
     ScoremapLoadConfig {
       ignore_unsupported_property: false,
@@ -93,11 +114,29 @@ pub fn lex<T>(
   config: ScoremapLoadConfig,
   reader: BufReader<T>,
 ) -> Result<Vec<Token>, ScoremapLexError>
+where
+  T: Read,
+{
+  let (tokens, errors, _) = lex_all(config, reader)?;
+  if let Some(err) = errors.into_iter().next() {
+    return Err(err);
+  }
+  Ok(tokens)
+}
+
+pub type LexOutput =
+  (Vec<Token>, Vec<ScoremapLexError>, Vec<ScoremapWarning>);
+
+// 最初のエラーで止まらずに、すべての行を字句解析する
+pub fn lex_all<T>(
+  config: ScoremapLoadConfig,
+  reader: BufReader<T>,
+) -> Result<LexOutput, ScoremapLexError>
 where
   T: Read,
 {
   use pattern::*;
-  use std::io::BufRead;
+  use std::io::{BufRead, ErrorKind};
   use ScoremapLexError::*;
 
   let comment_reg = Regex::new(COMMENT)?;
@@ -106,12 +145,18 @@ where
   let property_reg = Regex::new(PROPERTY)?;
   let property = Lexer::new(property_reg, property_lexer);
 
+  let tempo_reg = Regex::new(TEMPO)?;
+  let tempo = Lexer::new(tempo_reg, tempo_lexer);
+
   let command_reg = Regex::new(COMMAND)?;
   let command = Lexer::new(command_reg, command_lexer);
 
   let yomigana_reg = Regex::new(YOMIGANA)?;
   let yomigana = Lexer::new(yomigana_reg, yomigana_lexer);
 
+  let latin_reg = Regex::new(LATIN)?;
+  let latin = Lexer::new(latin_reg, latin_lexer);
+
   let caption_reg = Regex::new(CAPTION)?;
   let caption = Lexer::new(caption_reg, caption_lexer);
 
@@ -121,6 +166,9 @@ where
   let seconds_reg = Regex::new(SECONDS)?;
   let seconds = Lexer::new(seconds_reg, seconds_lexer);
 
+  let beat_reg = Regex::new(BEAT)?;
+  let beat = Lexer::new(beat_reg, beat_lexer);
+
   let minutes_reg = Regex::new(MINUTES)?;
   let minutes = Lexer::new(minutes_reg, minutes_lexer);
 
@@ -128,23 +176,38 @@ where
 
   let mut entire = comment
     .connect(seconds)
+    .connect(beat)
     .connect(minutes)
+    .connect(tempo)
     .connect(command)
     .connect(caption)
     .connect(property)
     .connect(yomigana)
+    .connect(latin)
     .connect(section)
     .connect(lyrics);
 
   let mut ctx = LexerCtx::new(config);
   let mut tokens: Vec<Token> = vec![];
+  let mut errors = vec![];
   for (line_num, line) in reader.lines().enumerate() {
     let line_num = line_num + 1; // starts from 1
-    let line = line.map_err(|_e| UnexpectedEndOfFile)?;
+    let line = match line {
+      Ok(line) => line,
+      Err(err) => {
+        errors.push(UnexpectedEndOfFile { line_num });
+        if err.kind() == ErrorKind::InvalidData {
+          continue;
+        }
+        break;
+      }
+    };
     ctx.set_line(line, line_num);
-    if let Some(token) = entire.lex(&mut ctx).transpose()? {
-      tokens.push(token);
+    match entire.lex(&mut ctx) {
+      Some(Ok(token)) => tokens.push(token),
+      Some(Err(err)) => errors.push(err),
+      None => {}
     }
   }
-  Ok(tokens)
+  Ok((tokens, errors, ctx.take_warnings()))
 }
@@ -1,20 +1,29 @@
 use super::{
-  super::token::TokenContent::*, ScoremapLexError, ScoremapLoadConfig,
+  super::token::TokenContent::*,
+  tempo::{Tempo, BEATS_PER_BAR, TICKS_PER_BEAT},
+  ScoremapLexError, ScoremapLoadConfig,
 };
 use crate::model::exp::{
-  scoremap::token::Token, sentence::roman::RomanStr,
+  scoremap::{
+    diagnostic::ScoremapWarning,
+    metadata::{parse_bpm, ScoremapMetadata},
+    token::Token,
+  },
+  sentence::roman::RomanStr,
   time::MinuteSecond,
 };
 use regex::Captures;
 use ScoremapLexError::*;
 
-const METADATA_KEYS: &[&str] = &[
+pub const METADATA_KEYS: &[&str] = &[
   "title",
   "song_author",
+  "lyrics_author",
   "singer",
   "score_author",
   "song_data",
   "bpm",
+  "offset",
 ];
 
 #[derive(Debug, Clone)]
@@ -23,6 +32,8 @@ pub struct LexerCtx {
   line: String,
   cfg: ScoremapLoadConfig,
   curr_mise: MinuteSecond,
+  tempo: Option<Tempo>,
+  warnings: Vec<ScoremapWarning>,
 }
 
 impl LexerCtx {
@@ -30,8 +41,10 @@ impl LexerCtx {
     Self {
       cfg: config,
       curr_mise: MinuteSecond::new(),
+      tempo: None,
       line_num: 0,
       line: "".into(),
+      warnings: vec![],
     }
   }
 
@@ -43,6 +56,10 @@ impl LexerCtx {
     self.line = line;
     self.line_num = line_num;
   }
+
+  pub fn take_warnings(&mut self) -> Vec<ScoremapWarning> {
+    std::mem::take(&mut self.warnings)
+  }
 }
 
 pub type TokenResult = Option<Result<Token, ScoremapLexError>>;
@@ -62,7 +79,13 @@ pub fn comment_lexer(
 pub const PROPERTY: &str = r"^:([[:^space:]]+)[[:space:]]+(.+)$";
 pub fn property_lexer(
   captures: Captures,
-  LexerCtx { line_num, cfg, .. }: &mut LexerCtx,
+  LexerCtx {
+    line_num,
+    cfg,
+    tempo,
+    warnings,
+    ..
+  }: &mut LexerCtx,
 ) -> TokenResult {
   let line_num = *line_num;
   if captures.len() != 3 {
@@ -72,9 +95,12 @@ pub fn property_lexer(
     }));
   }
   let key = captures.get(1)?.as_str().to_owned();
-  if !METADATA_KEYS.contains(&key.as_str()) {
+  if !METADATA_KEYS.contains(&key.as_str())
+    && !ScoremapMetadata::is_extension(&key)
+  {
     return Some(if cfg.ignore_unsupported_property {
-      println!("未対応のプロパティがありました。無視します。");
+      warnings
+        .push(ScoremapWarning::UnsupportedProperty { line_num });
       Ok(Token {
         line_num,
         content: Comment,
@@ -87,12 +113,47 @@ pub fn property_lexer(
     });
   }
   let value = captures.get(2)?.as_str().to_owned();
+  if let Err(reason) = ScoremapMetadata::validate(&key, &value) {
+    return Some(Err(InvalidPropertyValue { line_num, reason }));
+  }
+  if key == "bpm" {
+    *tempo = parse_bpm(&value).ok().map(Tempo::new);
+  }
   Some(Ok(Token {
     line_num,
     content: Property { key, value },
   }))
 }
 
+pub const TEMPO: &str = r"^[[:space:]]*\[[[:space:]]*bpm[[:space:]]+([^[:space:]\]]+)[[:space:]]*\][[:space:]]*$";
+pub fn tempo_lexer(
+  captures: Captures,
+  LexerCtx {
+    line_num,
+    curr_mise,
+    tempo,
+    ..
+  }: &mut LexerCtx,
+) -> TokenResult {
+  let line_num = *line_num;
+  let bpm = match parse_bpm(captures.get(1)?.as_str()) {
+    Ok(bpm) => bpm,
+    Err(reason) => {
+      return Some(Err(InvalidTempo { line_num, reason }))
+    }
+  };
+  // 拍は曲の始めから数えるので、今の位置までは前のテンポで進める
+  let at = curr_mise.as_seconds();
+  *tempo = Some(
+    tempo
+      .map_or_else(|| Tempo::new(bpm), |prev| prev.change(at, bpm)),
+  );
+  Some(Ok(Token {
+    line_num,
+    content: Comment,
+  }))
+}
+
 pub const COMMAND: &str =
   r"^[[:space:]]*\[[[:space:]]*(.*)[[:space:]]*\][[:space:]]*$";
 pub fn command_lexer(
@@ -106,7 +167,8 @@ pub fn command_lexer(
   }))
 }
 
-pub const YOMIGANA: &str = r"^:([あいうえおかきくけこさしすせそたちつてとなにぬねのはひふへほまみむめもやゆよらりるれろわゐゑをんぁぃぅぇぉゃゅょゎっーがぎぐげござじずぜぞだぢづでどばびぶべぼぱぴぷぺぽ]+)$";
+pub const YOMIGANA: &str =
+  r"^:([ぁ-ゖァ-ヶー、。・「」〜！-～a-zA-Z0-9\-,./!?'~\[\]]+)$";
 pub fn yomigana_lexer(
   captures: Captures,
   LexerCtx { line_num, .. }: &mut LexerCtx,
@@ -114,12 +176,8 @@ pub fn yomigana_lexer(
   let line_num = *line_num;
   let string = captures.get(1)?.as_str();
   let content = Yomigana({
-    let roman = RomanStr::new(string).map_err(|_e| {
-      InvalidStatementDefinition {
-        line_num,
-        reason: "ふりがなでのそのような平仮名の並びは未対応です。",
-      }
-    });
+    let roman = RomanStr::new(string)
+      .map_err(|err| InvalidYomigana { line_num, err });
     if let Err(err) = roman {
       return Some(Err(err));
     }
@@ -128,6 +186,18 @@ pub fn yomigana_lexer(
   Some(Ok(Token { line_num, content }))
 }
 
+pub const LATIN: &str = r"^=(.*)$";
+pub fn latin_lexer(
+  captures: Captures,
+  LexerCtx { line_num, .. }: &mut LexerCtx,
+) -> TokenResult {
+  let string = captures.get(1)?.as_str();
+  Some(Ok(Token {
+    line_num: *line_num,
+    content: Latin(string.trim().to_owned()),
+  }))
+}
+
 pub const CAPTION: &str =
   r"^[[:space:]]*>>[[:space:]]*(.+)[[:space:]]*$";
 pub fn caption_lexer(
@@ -150,7 +220,7 @@ pub fn section_lexer(
   let string = captures.get(1)?.as_str();
   Some(Ok(Token {
     line_num: *line_num,
-    content: Section(string.to_owned()),
+    content: Section(string.trim().to_owned()),
   }))
 }
 
@@ -161,31 +231,92 @@ pub fn seconds_lexer(
   LexerCtx {
     line_num,
     curr_mise,
+    warnings,
     ..
   }: &mut LexerCtx,
 ) -> TokenResult {
+  let line_num = *line_num;
   let num: f64 = {
     let num_res = captures
       .get(1)?
       .as_str()
       .parse()
-      .map_err(|_e| ParsingNumberFailure);
+      .map_err(|_e| ParsingNumberFailure { line_num });
     if let Err(err) = num_res {
       return Some(Err(err));
     }
     num_res.unwrap()
   };
+  advance(curr_mise.seconds(num), line_num, curr_mise, warnings)
+}
+
+fn advance(
+  specified: MinuteSecond,
+  line_num: usize,
+  curr_mise: &mut MinuteSecond,
+  warnings: &mut Vec<ScoremapWarning>,
+) -> TokenResult {
   // それ以前の時間指定は無視
-  if curr_mise.seconds(num) <= *curr_mise {
+  if specified <= *curr_mise {
+    if specified < *curr_mise {
+      warnings.push(ScoremapWarning::IgnoredTiming { line_num });
+    }
     return None;
   }
-  *curr_mise = curr_mise.seconds(num);
+  *curr_mise = specified;
   Some(Ok(Token {
-    line_num: *line_num,
+    line_num,
     content: Time(*curr_mise),
   }))
 }
 
+pub const BEAT: &str =
+  r"^\*[[:space:]]*([0-9]+):([0-9]+)(?::([0-9]+))?[[:space:]]*$";
+pub fn beat_lexer(
+  captures: Captures,
+  LexerCtx {
+    line_num,
+    curr_mise,
+    tempo,
+    warnings,
+    ..
+  }: &mut LexerCtx,
+) -> TokenResult {
+  let line_num = *line_num;
+  let number = |index| -> Option<u32> {
+    captures
+      .get(index)
+      .map_or(Some(0), |m| m.as_str().parse().ok())
+  };
+  let (bar, beat, tick) = match (number(1), number(2), number(3)) {
+    (Some(bar), Some(beat), Some(tick)) => (bar, beat, tick),
+    _ => return Some(Err(ParsingNumberFailure { line_num })),
+  };
+  let tempo = match tempo {
+    Some(tempo) => tempo,
+    None => {
+      return Some(Err(InvalidBeatTiming {
+        line_num,
+        reason: "拍での時間指定より前に bpm を指定してください。",
+      }))
+    }
+  };
+  if bar == 0 || beat == 0 || BEATS_PER_BAR < beat {
+    return Some(Err(InvalidBeatTiming {
+      line_num,
+      reason: "小節と拍は 1 から数えてください。",
+    }));
+  }
+  if TICKS_PER_BEAT <= tick {
+    return Some(Err(InvalidBeatTiming {
+      line_num,
+      reason: "ティックが 1 拍の長さを超えています。",
+    }));
+  }
+  let specified = tempo.position(bar, beat, tick).into();
+  advance(specified, line_num, curr_mise, warnings)
+}
+
 pub const MINUTES: &str =
   r"^\|[[:space:]]*([1-9][0-9]*)[[:space:]]*$";
 pub fn minutes_lexer(
@@ -197,11 +328,11 @@ pub fn minutes_lexer(
   }: &mut LexerCtx,
 ) -> TokenResult {
   let num: u32 = {
-    let num_res = captures
-      .get(1)?
-      .as_str()
-      .parse()
-      .map_err(|_e| ParsingNumberFailure);
+    let num_res = captures.get(1)?.as_str().parse().map_err(|_e| {
+      ParsingNumberFailure {
+        line_num: *line_num,
+      }
+    });
     if let Err(err) = num_res {
       return Some(Err(err));
     }
@@ -216,8 +347,18 @@ pub fn minutes_lexer(
 
 pub fn lyrics_lexer(
   _: Captures,
-  LexerCtx { line_num, line, .. }: &mut LexerCtx,
+  LexerCtx {
+    line_num,
+    line,
+    warnings,
+    ..
+  }: &mut LexerCtx,
 ) -> TokenResult {
+  if line.starts_with(':') {
+    warnings.push(ScoremapWarning::LyricsLikeYomigana {
+      line_num: *line_num,
+    });
+  }
   Some(Ok(Token {
     line_num: *line_num,
     content: Lyrics(line.to_owned()),
@@ -0,0 +1,60 @@
+use crate::model::exp::time::Seconds;
+
+pub const BEATS_PER_BAR: u32 = 4;
+pub const TICKS_PER_BEAT: u32 = 480;
+
+// 拍の位置と時間の対応
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Tempo {
+  bpm: f64,
+  origin: Seconds,
+  origin_beats: f64,
+}
+
+impl Tempo {
+  pub fn new(bpm: f64) -> Self {
+    Self {
+      bpm,
+      origin: 0.0.into(),
+      origin_beats: 0.0,
+    }
+  }
+
+  fn seconds_per_beat(&self) -> f64 {
+    60.0 / self.bpm
+  }
+
+  // at の時点から bpm に変える
+  pub fn change(&self, at: Seconds, bpm: f64) -> Self {
+    Self {
+      bpm,
+      origin: at,
+      origin_beats: self.origin_beats
+        + (at - self.origin).as_f64() / self.seconds_per_beat(),
+    }
+  }
+
+  // 小節と拍は 1 から数える
+  pub fn position(&self, bar: u32, beat: u32, tick: u32) -> Seconds {
+    // 大きな小節でも溢れないよう実数で数える
+    let beats = (bar as f64 - 1.0) * BEATS_PER_BAR as f64
+      + (beat as f64 - 1.0)
+      + tick as f64 / TICKS_PER_BEAT as f64;
+    self.origin
+      + Seconds::new(
+        (beats - self.origin_beats) * self.seconds_per_beat(),
+      )
+  }
+}
+
+#[test]
+fn tempo() {
+  let tempo = Tempo::new(120.0);
+  assert_eq!(0.0, tempo.position(1, 1, 0));
+  assert_eq!(0.75, tempo.position(1, 2, 240));
+  assert_eq!(4.0, tempo.position(3, 1, 0));
+
+  let tempo = tempo.change(4.0.into(), 60.0);
+  assert_eq!(5.0, tempo.position(3, 2, 0));
+  assert_eq!(8.0, tempo.position(4, 1, 0));
+}
@@ -196,6 +196,9 @@ fn pattern_tests() -> Result<(), super::ScoremapLexError> {
   assert!(reg.is_match(":てすと"));
   assert!(reg.is_match(":はんばーがー"));
   assert!(reg.is_match(":ぅゎょぅじょっょぃ"));
+  assert!(reg.is_match(":ヴァイオリン"));
+  assert!(reg.is_match(":ilove2ヶ、「ＹＯＵ」！"));
+  assert!(!reg.is_match(":title TEST"));
 
   let reg = Regex::new(CAPTION)?;
   assert!(reg.is_match(">>テスト"));
@@ -222,3 +225,58 @@ fn pattern_tests() -> Result<(), super::ScoremapLexError> {
 
   Ok(())
 }
+
+#[test]
+fn beat() -> Result<(), ScoremapLexError> {
+  let reader = BufReader::new(
+    r#"
+:bpm 120
+
+[start]
+*1:2
+>>テスト
+*3:1:240
+[bpm 60]
+*4:1
+*5:4:0
+*41:1
+[end]
+"#
+    .as_bytes(),
+  );
+  let times: Vec<_> = lex(ScoremapLoadConfig::new(), reader)?
+    .into_iter()
+    .filter_map(|token| match token.content {
+      TokenContent::Time(time) => Some(time.as_seconds()),
+      _ => None,
+    })
+    .collect();
+  assert_eq!(0.5, times[0]);
+  assert_eq!(4.25, times[1]);
+  assert_eq!(7.75, times[2]);
+  assert_eq!(14.75, times[3]);
+  assert_eq!(155.75, times[4]);
+  assert_eq!(5, times.len());
+
+  let reader = BufReader::new("[start]\n*1:1:0\n".as_bytes());
+  assert!(matches!(
+    lex(ScoremapLoadConfig::new(), reader),
+    Err(ScoremapLexError::InvalidBeatTiming { line_num: 2, .. })
+  ));
+
+  // 拍の数が u32 に収まらない小節
+  let reader = BufReader::new(
+    ":bpm 120\n[start]\n*2000000000:1\n[end]\n".as_bytes(),
+  );
+  let times: Vec<_> = lex(ScoremapLoadConfig::new(), reader)?
+    .into_iter()
+    .filter_map(|token| match token.content {
+      TokenContent::Time(time) => Some(time.as_seconds()),
+      _ => None,
+    })
+    .collect();
+  assert_eq!(times.len(), 1);
+  assert_eq!(times[0].as_millis(), 3_999_999_998_000);
+
+  Ok(())
+}
@@ -0,0 +1,181 @@
+use super::lexer::METADATA_KEYS;
+use crate::model::exp::time::Seconds;
+use std::{collections::HashMap, path::PathBuf};
+
+#[cfg(test)]
+mod tests;
+
+pub const EXTENSION_PREFIX: &str = "x-";
+
+const SONG_DATA_EXTENSIONS: &[&str] = &["wav", "ogg", "mp3", "flac"];
+
+#[readonly::make]
+#[derive(Debug, PartialEq, Clone)]
+pub struct MusicInfo {
+  pub title: Option<String>,
+  pub song_author: Option<String>,
+  pub lyricist: Option<String>,
+  pub singer: Option<String>,
+  pub score_author: Option<String>,
+}
+
+#[readonly::make]
+#[derive(Debug, Default, PartialEq, Clone)]
+pub struct ScoremapMetadata {
+  pub title: Option<String>,
+  pub song_author: Option<String>,
+  pub lyrics_author: Option<String>,
+  pub singer: Option<String>,
+  pub score_author: Option<String>,
+  pub song_data: Option<PathBuf>,
+  pub bpm: Option<f64>,
+  // 音源に対して歌詞をずらす秒数
+  pub offset: Seconds,
+  pub extensions: HashMap<String, String>,
+}
+
+fn parse_text(value: &str) -> Result<String, &'static str> {
+  let value = value.trim();
+  if value.is_empty() {
+    return Err("プロパティの値が空です。");
+  }
+  Ok(value.into())
+}
+
+fn parse_song_data(value: &str) -> Result<PathBuf, &'static str> {
+  let path = PathBuf::from(value.trim());
+  let supported = path
+    .extension()
+    .and_then(|extension| extension.to_str())
+    .is_some_and(|extension| {
+      SONG_DATA_EXTENSIONS
+        .contains(&extension.to_lowercase().as_str())
+    });
+  if !supported {
+    return Err(
+      "song_data には wav、ogg、mp3、flac の音声ファイルを指定してください。",
+    );
+  }
+  Ok(path)
+}
+
+pub fn parse_bpm(value: &str) -> Result<f64, &'static str> {
+  value
+    .trim()
+    .parse()
+    .ok()
+    .filter(|bpm: &f64| bpm.is_finite() && 0.0 < *bpm)
+    .ok_or("bpm には正の数を指定してください。")
+}
+
+fn parse_offset(value: &str) -> Result<Seconds, &'static str> {
+  value
+    .trim()
+    .parse::<f64>()
+    .ok()
+    .filter(|offset| offset.is_finite())
+    .map(Seconds::new)
+    .ok_or("offset には秒数を指定してください。")
+}
+
+fn format_offset(offset: Seconds) -> String {
+  let millis = offset.as_millis();
+  format!(
+    "{}{}.{:03}",
+    if millis < 0 { "-" } else { "" },
+    millis.abs() / 1000,
+    millis.abs() % 1000
+  )
+}
+
+impl ScoremapMetadata {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  // 値を検証してから設定する
+  pub fn insert(
+    &mut self,
+    key: &str,
+    value: &str,
+  ) -> Result<(), &'static str> {
+    match key {
+      "title" => self.title = Some(parse_text(value)?),
+      "song_author" => self.song_author = Some(parse_text(value)?),
+      "lyrics_author" => {
+        self.lyrics_author = Some(parse_text(value)?)
+      }
+      "singer" => self.singer = Some(parse_text(value)?),
+      "score_author" => self.score_author = Some(parse_text(value)?),
+      "song_data" => self.song_data = Some(parse_song_data(value)?),
+      "bpm" => self.bpm = Some(parse_bpm(value)?),
+      "offset" => self.offset = parse_offset(value)?,
+      _ if Self::is_extension(key) => {
+        self.extensions.insert(key.into(), parse_text(value)?);
+      }
+      _ => return Err("未対応のプロパティです。"),
+    }
+    Ok(())
+  }
+
+  pub fn validate(
+    key: &str,
+    value: &str,
+  ) -> Result<(), &'static str> {
+    Self::new().insert(key, value)
+  }
+
+  pub fn is_extension(key: &str) -> bool {
+    EXTENSION_PREFIX.len() < key.len()
+      && key.starts_with(EXTENSION_PREFIX)
+  }
+
+  // 設定されているプロパティを METADATA_KEYS の順に、拡張は名前順に返す
+  pub fn properties(&self) -> Vec<(String, String)> {
+    let mut properties: Vec<(String, String)> = METADATA_KEYS
+      .iter()
+      .filter_map(|&key| {
+        let value = match key {
+          "title" => self.title.clone(),
+          "song_author" => self.song_author.clone(),
+          "lyrics_author" => self.lyrics_author.clone(),
+          "singer" => self.singer.clone(),
+          "score_author" => self.score_author.clone(),
+          "song_data" => self
+            .song_data
+            .as_ref()
+            .map(|path| path.to_string_lossy().into()),
+          "bpm" => self.bpm.map(|bpm| bpm.to_string()),
+          "offset" if self.offset.as_millis() != 0 => {
+            Some(format_offset(self.offset))
+          }
+          _ => None,
+        }?;
+        Some((key.to_owned(), value))
+      })
+      .collect();
+    let mut extensions: Vec<_> = self
+      .extensions
+      .iter()
+      .map(|(key, value)| (key.clone(), value.clone()))
+      .collect();
+    extensions.sort();
+    properties.append(&mut extensions);
+    properties
+  }
+
+  // 譜面の時間を factor 倍にしたとき、ずらす秒数も合わせる
+  pub fn scale_time(&mut self, factor: f64) {
+    self.offset = (self.offset.as_f64() * factor).into();
+  }
+
+  pub fn get_music_info(&self) -> MusicInfo {
+    MusicInfo {
+      title: self.title.clone(),
+      song_author: self.song_author.clone(),
+      lyricist: self.lyrics_author.clone(),
+      singer: self.singer.clone(),
+      score_author: self.score_author.clone(),
+    }
+  }
+}
@@ -0,0 +1,50 @@
+use super::ScoremapMetadata;
+use std::path::PathBuf;
+
+#[test]
+fn typed_properties() -> Result<(), &'static str> {
+  let mut metadata = ScoremapMetadata::new();
+  metadata.insert("title", "  キミのチカラ ")?;
+  metadata.insert("lyrics_author", "佐々木英州")?;
+  metadata.insert("song_data", "music/kiminochikara.WAV")?;
+  metadata.insert("bpm", "120")?;
+  metadata.insert("offset", "-00.250")?;
+  metadata.insert("x-genre", "ボカロ")?;
+
+  assert_eq!(metadata.title.as_deref(), Some("キミのチカラ"));
+  assert_eq!(
+    metadata.song_data,
+    Some(PathBuf::from("music/kiminochikara.WAV"))
+  );
+  assert_eq!(metadata.bpm, Some(120.0));
+  assert_eq!(-0.25, metadata.offset);
+  assert_eq!(metadata.extensions["x-genre"], "ボカロ");
+  assert_eq!(
+    metadata.properties(),
+    vec![
+      ("title".into(), "キミのチカラ".into()),
+      ("lyrics_author".into(), "佐々木英州".into()),
+      ("song_data".into(), "music/kiminochikara.WAV".into()),
+      ("bpm".into(), "120".into()),
+      ("offset".into(), "-0.250".into()),
+      ("x-genre".into(), "ボカロ".into()),
+    ]
+  );
+
+  let info = metadata.get_music_info();
+  assert_eq!(info.lyricist.as_deref(), Some("佐々木英州"));
+  assert_eq!(info.singer, None);
+  Ok(())
+}
+
+#[test]
+fn invalid_properties() {
+  assert!(ScoremapMetadata::validate("bpm", "速い").is_err());
+  assert!(ScoremapMetadata::validate("bpm", "-120").is_err());
+  assert!(ScoremapMetadata::validate("offset", "0:25").is_err());
+  assert!(
+    ScoremapMetadata::validate("song_data", "song.txt").is_err()
+  );
+  assert!(ScoremapMetadata::validate("x-", "値").is_err());
+  assert!(ScoremapMetadata::validate("genre", "ボカロ").is_err());
+}
@@ -1,4 +1,5 @@
 use super::{
+  diagnostic::ScoremapWarning,
   sections::{section::note::Note, Sections},
   token::{Token, TokenContent},
   Scoremap,
@@ -11,6 +12,7 @@ mod processor;
 #[cfg(test)]
 mod tests;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum ScoremapParseError {
   InvalidCommand {
@@ -74,6 +76,18 @@ impl Parser {
 pub fn parse(
   tokens: &[Token],
 ) -> Result<Scoremap, ScoremapParseError> {
+  let (scoremap, errors, _) = parse_all(tokens);
+  if let Some(err) = errors.into_iter().next() {
+    return Err(err);
+  }
+  Ok(scoremap)
+}
+
+pub type ParseOutput =
+  (Scoremap, Vec<ScoremapParseError>, Vec<ScoremapWarning>);
+
+// 最初のエラーで止まらずに、すべてのトークンを構文解析する
+pub fn parse_all(tokens: &[Token]) -> ParseOutput {
   use processor::*;
 
   let mut tokens: VecDeque<_> = tokens
@@ -101,6 +115,8 @@ pub fn parse(
 
   let yomigana_parser: Parser = Parser::new(yomigana_processor);
 
+  let latin_parser: Parser = Parser::new(latin_processor);
+
   let section_parser: Parser = Parser::new(section_processor);
 
   let lyrics_parser: Parser = Parser::new(lyrics_processor);
@@ -113,16 +129,23 @@ pub fn parse(
     .connect(caption_parser)
     .connect(property_parser)
     .connect(yomigana_parser)
+    .connect(latin_parser)
     .connect(section_parser)
     .connect(lyrics_parser)
     .connect(comment_parser);
 
   let mut ctx = ParserCtx::new();
+  let mut errors = vec![];
   while !tokens.is_empty() {
-    if let Some(note) =
-      parser.parse(&mut tokens, &mut ctx).transpose()?
-    {
-      ctx.notes.push(note);
+    let remaining = tokens.len();
+    match parser.parse(&mut tokens, &mut ctx) {
+      Some(Ok(note)) => ctx.notes.push(note),
+      Some(Err(err)) => errors.push(err),
+      None => {}
+    }
+    if remaining == tokens.len() {
+      // どの処理にも消費されなかったトークンは読み飛ばす
+      tokens.pop_front();
     }
   }
   // 最後に空白ノーツを追加
@@ -131,10 +154,16 @@ pub fn parse(
   {
     ctx.notes.push(Note::blank(last_duration));
   }
-  ctx.sections.push(ctx.notes);
+  if !ctx.notes.is_empty() {
+    ctx.sections.push((ctx.section_name, ctx.notes));
+  }
 
-  Ok(Scoremap {
-    metadata: ctx.metadata,
-    sections: Sections::new(ctx.sections),
-  })
+  (
+    Scoremap {
+      metadata: ctx.metadata,
+      sections: Sections::new(ctx.sections),
+    },
+    errors,
+    ctx.warnings,
+  )
 }
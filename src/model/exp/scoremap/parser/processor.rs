@@ -2,6 +2,7 @@ use super::ScoremapParseError;
 use crate::model::exp::{
   note::Note,
   scoremap::{
+    diagnostic::ScoremapWarning,
     token::{Token, TokenContent},
     ScoremapMetadata,
   },
@@ -14,8 +15,10 @@ use ScoremapParseError::*;
 #[derive(Debug, Clone)]
 pub(super) struct ParserCtx {
   pub(super) metadata: ScoremapMetadata,
-  pub(super) sections: Vec<Vec<Note>>,
+  pub(super) sections: Vec<(String, Vec<Note>)>,
+  pub(super) section_name: String,
   pub(super) notes: Vec<Note>,
+  pub(super) warnings: Vec<ScoremapWarning>,
   parsing_lyrics: bool,
   parsed_japanese: Option<String>,
   curr_time: MinuteSecond,
@@ -26,7 +29,9 @@ impl ParserCtx {
     Self {
       metadata: ScoremapMetadata::new(),
       sections: vec![],
+      section_name: "".into(),
       notes: vec![],
+      warnings: vec![],
       parsing_lyrics: false,
       parsed_japanese: None,
       curr_time: MinuteSecond::new(),
@@ -111,7 +116,6 @@ pub(super) fn single_time_processor(
       ..
     } = ctx;
     if !*parsing_lyrics {
-      eprintln!("{:?}", tokens);
       return Some(Err(InvalidTimingDefinition {
         line_num: *line_num,
         reason: "時間指定は歌詞定義の中のみ有効です。",
@@ -132,7 +136,11 @@ pub(super) fn single_time_processor(
 
 pub(super) fn command_processor(
   tokens: &mut VecDeque<&Token>,
-  ParserCtx { parsing_lyrics, .. }: &mut ParserCtx,
+  ParserCtx {
+    parsing_lyrics,
+    warnings,
+    ..
+  }: &mut ParserCtx,
 ) -> ParseResult {
   if let Some(Token {
     content: TokenContent::Command(command),
@@ -152,7 +160,10 @@ pub(super) fn command_processor(
         }
         *parsing_lyrics = true;
       }
-      "break" => {}
+      "break" => {
+        warnings
+          .push(ScoremapWarning::DeprecatedCommand { line_num });
+      }
       "end" => {
         if !*parsing_lyrics {
           return Some(Err(InvalidCommand {
@@ -216,7 +227,12 @@ pub(super) fn property_processor(
         reason: "プロパティの指定は歌詞定義の外のみ有効です。",
       }));
     }
-    metadata.0.insert(key.clone(), value.clone());
+    if let Err(reason) = metadata.insert(key, value) {
+      return Some(Err(InvalidPropertyDefinition {
+        line_num: *line_num,
+        reason,
+      }));
+    }
   }
   None
 }
@@ -239,7 +255,9 @@ pub(super) fn yomigana_processor(
       let sentence =
         Sentence::from(lyrics.as_str(), yomigana.clone());
       *parsed_japanese = None;
-      return Some(Ok(Note::sentence(duration, sentence)));
+      return Some(Ok(
+        Note::sentence(duration, sentence).at_line(*line_num),
+      ));
     }
     return Some(Err(InvalidStatementDefinition {
       line_num: *line_num,
@@ -249,22 +267,57 @@ pub(super) fn yomigana_processor(
   None
 }
 
+pub(super) fn latin_processor(
+  tokens: &mut VecDeque<&Token>,
+  ctx: &mut ParserCtx,
+) -> ParseResult {
+  if let Some(Token {
+    content: TokenContent::Latin(latin),
+    line_num,
+  }) = tokens.front()
+  {
+    let duration = ctx.calc_duration(tokens, *line_num).ok()?;
+    tokens.remove(0);
+    let ParserCtx {
+      parsed_japanese, ..
+    } = ctx;
+    if let Some(lyrics) = parsed_japanese {
+      // 省略したときは歌詞をそのまま打つ
+      let to_input = if latin.is_empty() { &*lyrics } else { latin };
+      let sentence = Sentence::latin(lyrics.as_str(), to_input);
+      *parsed_japanese = None;
+      return Some(Ok(
+        Note::sentence(duration, sentence).at_line(*line_num),
+      ));
+    }
+    return Some(Err(InvalidStatementDefinition {
+      line_num: *line_num,
+      reason: "打つ文字列は歌詞より後にしてください。",
+    }));
+  }
+  None
+}
+
 pub(super) fn section_processor(
   tokens: &mut VecDeque<&Token>,
   ParserCtx {
-    notes, sections, ..
+    notes,
+    sections,
+    section_name,
+    ..
   }: &mut ParserCtx,
 ) -> ParseResult {
   if let Some(Token {
-    content: TokenContent::Section(_),
+    content: TokenContent::Section(name),
     ..
   }) = tokens.front()
   {
     tokens.remove(0);
     if !notes.is_empty() {
-      sections.push(notes.clone());
+      sections.push((section_name.clone(), notes.clone()));
       *notes = vec![];
     }
+    *section_name = name.clone();
   }
   None
 }
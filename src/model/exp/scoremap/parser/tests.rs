@@ -6,7 +6,6 @@ use crate::model::exp::{
   scoremap::{sections::Sections, Scoremap, ScoremapMetadata},
   time::{Duration, MinuteSecond},
 };
-use std::collections::HashMap;
 
 #[test]
 fn case1() -> Result<(), ScoremapParseError> {
@@ -689,244 +688,267 @@ fn case1() -> Result<(), ScoremapParseError> {
   let mut dur = Duration::new(0.0, 18.6).unwrap();
   let expected = Scoremap {
     metadata: {
-      let mut m: HashMap<String, String> = HashMap::new();
-      m.insert("title".into(), "満点星の約束".into());
-      m.insert("song_author".into(), "Mikuro さいな".into());
-      m.insert("singer".into(), "塩音ルト CERANA".into());
-      m.insert("score_author".into(), "Mikuro さいな".into());
-      m.insert("song_data".into(), "twinkle-grace.ogg".into());
-      m.insert("bpm".into(), "200".into());
-      ScoremapMetadata(m)
+      let mut m = ScoremapMetadata::new();
+      m.insert("title", "満点星の約束").unwrap();
+      m.insert("song_author", "Mikuro さいな").unwrap();
+      m.insert("singer", "塩音ルト CERANA").unwrap();
+      m.insert("score_author", "Mikuro さいな").unwrap();
+      m.insert("song_data", "twinkle-grace.ogg").unwrap();
+      m.insert("bpm", "200").unwrap();
+      m
     },
     sections: Sections::new(vec![
-      vec![Note::caption(dur.clone(), "満点星の約束")],
-      vec![
-        Note::sentence(
-          dur.following_replace(4.9),
-          Sentence::new(
-            "体が浮くような 3000m の星空",
-            "からだがうくようなさんぜんめーとるのほしぞら",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(5.0),
-          Sentence::new(
-            "初めて来た日に 交わした願いが光る",
-            "はじめてきたひにかわしたねがいがひかる",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(4.8),
-          Sentence::new(
-            "遠くの街の光が 煌めきを隠して",
-            "とおくのまちのひかりがきらめきをかくして",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(4.7),
-          Sentence::new(
-            "今夜も少しずつ 希望が融けてゆく",
-            "こんやもすこしきぼうずつきぼうがとけてゆく",
-          )
-          .unwrap(),
-        ),
-      ],
-      vec![
-        Note::sentence(
-          dur.following_replace(4.7),
-          Sentence::new(
-            "ああ僕ひとりの力じゃ 何にもできないだろうさ",
-            "ああぼくひとりのちからじゃなんにもできないだろうさ",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(4.9),
-          Sentence::new(
-            "築いた繋がりなら 宇宙を越えられる",
-            "きづいたつながりならうちゅうをこえられる",
-          )
-          .unwrap(),
-        ),
-      ],
-      vec![
-        Note::sentence(
-          dur.following_replace(2.9),
-          Sentence::new(
-            "これから幾千 輝く光年",
-            "これからいくせんかがやくこうねん",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(2.6),
-          Sentence::new(
-            "あなたの願いは破壊神",
-            "あなたのねがいははかいしん",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(2.5),
-          Sentence::new(
-            "今まで遁走 隠した暴走",
-            "いままでとんそうかくしたぼうそう",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(2.8),
-          Sentence::new("してきたのにね", "してきたのにね").unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(2.7),
-          Sentence::new(
-            "これでも幾千 してきた我慢",
-            "これでもいくせんしてきたがまん",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(2.8),
-          Sentence::new(
-            "でもやっぱりためらうや",
-            "でもやっぱりためらうや",
-          )
-          .unwrap(),
-        ),
-        Note::blank(dur.following_replace(0.8)),
-        Note::sentence(
-          dur.following_replace(4.0),
-          Sentence::new(
-            "もっといい未来はもう無い",
-            "もっといいみらいはもうない",
-          )
-          .unwrap(),
-        ),
-        Note::caption(dur.following_replace(17.2), "(間奏)"),
-      ],
-      vec![Note::blank(dur.following_replace(2.0))],
-      vec![
-        Note::sentence(
-          dur.following_replace(4.8),
-          Sentence::new(
-            "内に惑う思い その原点に",
-            "うちにまどうおもいそのげんてんに",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(4.8),
-          Sentence::new(
-            "触れ写しても 心は得られない",
-            "ふれうつしてもこころはえられない",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(4.8),
-          Sentence::new(
-            "ひた走った海岸も 今では馬鹿らしくて",
-            "ひたはしったかいがんもいまではばからしくて",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(4.6),
-          Sentence::new(
-            "透明なこのファージは 山となって積み上がる",
-            "とうめいなこのふぁーじはやまとなってつみあがる",
-          )
-          .unwrap(),
-        ),
-      ],
-      vec![
-        Note::sentence(
-          dur.following_replace(4.4),
-          Sentence::new(
-            "唯一、力だけが無い この不思議な僕たちは",
-            "ゆいいつちからだけがないこのふしぎなぼくたちは",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(4.3),
-          Sentence::new(
-            "紡いだ回路だけで 不条理を壊せる",
-            "つむいだかいろだけでふじょうりをこわせる",
-          )
-          .unwrap(),
-        ),
-      ],
-      vec![
-        Note::sentence(
-          dur.following_replace(2.4),
-          Sentence::new(
-            "これまで幾千 交わした沿線",
-            "これまでいくせんかわしたえんせん",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(2.4),
-          Sentence::new(
-            "あなたとは友じゃいられない",
-            "あなたとはともじゃいられない",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(2.5),
-          Sentence::new(
-            "今まで棒線 引いた凱旋",
-            "いままでぼうせんひいたがいせん",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(2.6),
-          Sentence::new("してきたのにさ", "してきたのにさ").unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(2.7),
-          Sentence::new(
-            "これでも幾千 してきた我慢",
-            "これでもいくせんしてきたがまん",
-          )
-          .unwrap(),
-        ),
-        Note::sentence(
-          dur.following_replace(2.6),
-          Sentence::new(
-            "でもやっぱりためらうや",
-            "でもやっぱりためらうや",
-          )
-          .unwrap(),
-        ),
-        Note::blank(dur.following_replace(0.7)),
-        Note::sentence(
-          dur.following_replace(3.9),
-          Sentence::new(
-            "嘘偽りの上でしか",
-            "うそいつわりのうえでしか",
-          )
-          .unwrap(),
-        ),
-        Note::blank(dur.following_replace(8.9)),
-        Note::sentence(
-          dur.following_replace(1.7),
-          Sentence::new("生きられない", "いきられない").unwrap(),
-        ),
-        Note::blank(dur.following_replace(1.0)),
-      ],
+      (
+        "一番".into(),
+        vec![Note::caption(dur.clone(), "満点星の約束")],
+      ),
+      (
+        "Aメロ".into(),
+        vec![
+          Note::sentence(
+            dur.following_replace(4.9),
+            Sentence::new(
+              "体が浮くような 3000m の星空",
+              "からだがうくようなさんぜんめーとるのほしぞら",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(5.0),
+            Sentence::new(
+              "初めて来た日に 交わした願いが光る",
+              "はじめてきたひにかわしたねがいがひかる",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(4.8),
+            Sentence::new(
+              "遠くの街の光が 煌めきを隠して",
+              "とおくのまちのひかりがきらめきをかくして",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(4.7),
+            Sentence::new(
+              "今夜も少しずつ 希望が融けてゆく",
+              "こんやもすこしきぼうずつきぼうがとけてゆく",
+            )
+            .unwrap(),
+          ),
+        ],
+      ),
+      (
+        "Bメロ".into(),
+        vec![
+          Note::sentence(
+            dur.following_replace(4.7),
+            Sentence::new(
+              "ああ僕ひとりの力じゃ 何にもできないだろうさ",
+              "ああぼくひとりのちからじゃなんにもできないだろうさ",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(4.9),
+            Sentence::new(
+              "築いた繋がりなら 宇宙を越えられる",
+              "きづいたつながりならうちゅうをこえられる",
+            )
+            .unwrap(),
+          ),
+        ],
+      ),
+      (
+        "サビ".into(),
+        vec![
+          Note::sentence(
+            dur.following_replace(2.9),
+            Sentence::new(
+              "これから幾千 輝く光年",
+              "これからいくせんかがやくこうねん",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(2.6),
+            Sentence::new(
+              "あなたの願いは破壊神",
+              "あなたのねがいははかいしん",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(2.5),
+            Sentence::new(
+              "今まで遁走 隠した暴走",
+              "いままでとんそうかくしたぼうそう",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(2.8),
+            Sentence::new("してきたのにね", "してきたのにね")
+              .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(2.7),
+            Sentence::new(
+              "これでも幾千 してきた我慢",
+              "これでもいくせんしてきたがまん",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(2.8),
+            Sentence::new(
+              "でもやっぱりためらうや",
+              "でもやっぱりためらうや",
+            )
+            .unwrap(),
+          ),
+          Note::blank(dur.following_replace(0.8)),
+          Note::sentence(
+            dur.following_replace(4.0),
+            Sentence::new(
+              "もっといい未来はもう無い",
+              "もっといいみらいはもうない",
+            )
+            .unwrap(),
+          ),
+          Note::caption(dur.following_replace(17.2), "(間奏)"),
+        ],
+      ),
+      ("二番".into(), vec![Note::blank(dur.following_replace(2.0))]),
+      (
+        "Aメロ".into(),
+        vec![
+          Note::sentence(
+            dur.following_replace(4.8),
+            Sentence::new(
+              "内に惑う思い その原点に",
+              "うちにまどうおもいそのげんてんに",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(4.8),
+            Sentence::new(
+              "触れ写しても 心は得られない",
+              "ふれうつしてもこころはえられない",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(4.8),
+            Sentence::new(
+              "ひた走った海岸も 今では馬鹿らしくて",
+              "ひたはしったかいがんもいまではばからしくて",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(4.6),
+            Sentence::new(
+              "透明なこのファージは 山となって積み上がる",
+              "とうめいなこのふぁーじはやまとなってつみあがる",
+            )
+            .unwrap(),
+          ),
+        ],
+      ),
+      (
+        "Bメロ".into(),
+        vec![
+          Note::sentence(
+            dur.following_replace(4.4),
+            Sentence::new(
+              "唯一、力だけが無い この不思議な僕たちは",
+              "ゆいいつちからだけがないこのふしぎなぼくたちは",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(4.3),
+            Sentence::new(
+              "紡いだ回路だけで 不条理を壊せる",
+              "つむいだかいろだけでふじょうりをこわせる",
+            )
+            .unwrap(),
+          ),
+        ],
+      ),
+      (
+        "サビ".into(),
+        vec![
+          Note::sentence(
+            dur.following_replace(2.4),
+            Sentence::new(
+              "これまで幾千 交わした沿線",
+              "これまでいくせんかわしたえんせん",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(2.4),
+            Sentence::new(
+              "あなたとは友じゃいられない",
+              "あなたとはともじゃいられない",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(2.5),
+            Sentence::new(
+              "今まで棒線 引いた凱旋",
+              "いままでぼうせんひいたがいせん",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(2.6),
+            Sentence::new("してきたのにさ", "してきたのにさ")
+              .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(2.7),
+            Sentence::new(
+              "これでも幾千 してきた我慢",
+              "これでもいくせんしてきたがまん",
+            )
+            .unwrap(),
+          ),
+          Note::sentence(
+            dur.following_replace(2.6),
+            Sentence::new(
+              "でもやっぱりためらうや",
+              "でもやっぱりためらうや",
+            )
+            .unwrap(),
+          ),
+          Note::blank(dur.following_replace(0.7)),
+          Note::sentence(
+            dur.following_replace(3.9),
+            Sentence::new(
+              "嘘偽りの上でしか",
+              "うそいつわりのうえでしか",
+            )
+            .unwrap(),
+          ),
+          Note::blank(dur.following_replace(8.9)),
+          Note::sentence(
+            dur.following_replace(1.7),
+            Sentence::new("生きられない", "いきられない").unwrap(),
+          ),
+          Note::blank(dur.following_replace(1.0)),
+        ],
+      ),
     ]),
   };
   let actual = parse(input.as_slice())?;
 
-  assert_eq!(expected.metadata.0, actual.metadata.0);
+  assert_eq!(expected.metadata, actual.metadata);
 
   for (section_idx, (expected, actual)) in expected
     .sections
@@ -952,6 +974,12 @@ fn case1() -> Result<(), ScoremapParseError> {
         note_idx
       );
     }
+    assert_eq!(
+      expected.name(),
+      actual.name(),
+      "section_idx: {}",
+      section_idx
+    );
     assert_eq!(
       expected.len(),
       actual.len(),
@@ -1057,30 +1085,33 @@ fn case2() -> Result<(), ScoremapParseError> {
   let mut duration = Duration::new(0.0, 2.22).unwrap();
   let expected = Scoremap {
     metadata: {
-      let mut m: HashMap<String, String> = HashMap::new();
-      m.insert("title".into(), "TEST".into());
-      m.insert("score_author".into(), "Mikuro さいな".into());
-      m.insert("song_data".into(), "void.ogg".into());
-      m.insert("bpm".into(), "222.22".into());
-      ScoremapMetadata(m)
-    },
-    sections: Sections::new(vec![vec![
-      Note::blank(duration.clone()),
-      Note::sentence(
-        duration.following_replace(0.78),
-        Sentence::new("打鍵テスト", "だけんてすと").unwrap(),
-      ),
-      Note::blank(duration.following_replace(1.0)),
-      Note::sentence(
-        duration.following_replace(2.0),
-        Sentence::new("えっ", "えっ").unwrap(),
-      ),
-      Note::blank(duration.following_replace(1.0)),
-    ]]),
+      let mut m = ScoremapMetadata::new();
+      m.insert("title", "TEST").unwrap();
+      m.insert("score_author", "Mikuro さいな").unwrap();
+      m.insert("song_data", "void.ogg").unwrap();
+      m.insert("bpm", "222.22").unwrap();
+      m
+    },
+    sections: Sections::new(vec![(
+      "".into(),
+      vec![
+        Note::blank(duration.clone()),
+        Note::sentence(
+          duration.following_replace(0.78),
+          Sentence::new("打鍵テスト", "だけんてすと").unwrap(),
+        ),
+        Note::blank(duration.following_replace(1.0)),
+        Note::sentence(
+          duration.following_replace(2.0),
+          Sentence::new("えっ", "えっ").unwrap(),
+        ),
+        Note::blank(duration.following_replace(1.0)),
+      ],
+    )]),
   };
   let actual = parse(input.as_slice())?;
 
-  assert_eq!(expected.metadata.0, actual.metadata.0);
+  assert_eq!(expected.metadata, actual.metadata);
 
   for (section_idx, (expected, actual)) in expected
     .sections
@@ -1106,6 +1137,12 @@ fn case2() -> Result<(), ScoremapParseError> {
         note_idx
       );
     }
+    assert_eq!(
+      expected.name(),
+      actual.name(),
+      "section_idx: {}",
+      section_idx
+    );
     assert_eq!(
       expected.len(),
       actual.len(),
@@ -1,6 +1,6 @@
 use crate::model::exp::time::Seconds;
 use section::{
-  note::{Note, TypeResult},
+  note::{sentence::Sentence, Note, TypeResult},
   Section,
 };
 
@@ -13,13 +13,14 @@ pub struct Sections {
 }
 
 impl Sections {
-  pub fn new(notes: Vec<Vec<Note>>) -> Self {
+  pub fn new(notes: Vec<(String, Vec<Note>)>) -> Self {
     let sections: Vec<_> = notes
       .into_iter()
-      .map(|section| {
+      .map(|(name, section)| {
         let first = section.first().unwrap().duration();
         let last = section.last().unwrap().duration();
-        Section::new(section.clone(), first.concat(last))
+        let duration = first.concat(last);
+        Section::new(name, section, duration)
       })
       .collect();
     Self {
@@ -32,12 +33,16 @@ impl Sections {
     self.sections.get(self.current_section_index)
   }
 
-  pub fn input(&mut self, typed: char) -> TypeResult {
+  pub fn current_section_index(&self) -> usize {
+    self.current_section_index
+  }
+
+  pub fn input(&mut self, typed: char, time: Seconds) -> TypeResult {
     use TypeResult::*;
     if let Some(section) =
       self.sections.get_mut(self.current_section_index)
     {
-      section.input(typed)
+      section.input(typed, time)
     } else {
       Vacant
     }
@@ -59,11 +64,32 @@ impl Sections {
     }
   }
 
+  pub fn scale_time(&mut self, factor: f64) {
+    for section in &mut self.sections {
+      section.scale_time(factor);
+    }
+  }
+
+  // time より後に終わるノーツを、initial の同じノーツに戻す
+  pub fn reset_after(&mut self, time: Seconds, initial: &Sections) {
+    for (section, initial) in
+      self.sections.iter_mut().zip(&initial.sections)
+    {
+      section.reset_after(&time, initial);
+    }
+  }
+
+  pub fn sentences_mut(
+    &mut self,
+  ) -> impl Iterator<Item = (usize, &mut Sentence)> {
+    self.sections.iter_mut().flat_map(Section::sentences_mut)
+  }
+
   pub fn iter(&self) -> impl Iterator<Item = &Section> {
     self.sections.iter()
   }
 
-  #[allow(dead_code)]
+  #[allow(dead_code, clippy::len_without_is_empty)]
   pub fn len(&self) -> usize {
     self.sections.len()
   }
@@ -1,34 +1,67 @@
 use crate::model::exp::time::{Duration, Seconds};
-use note::{Note, NoteId, TypeResult};
+use note::{sentence::Sentence, Note, NoteId, NoteLog, TypeResult};
 
 pub mod note;
 
 #[derive(Debug, Clone)]
 pub struct Section {
+  name: String,
   notes: Vec<Note>,
   current_note_index: usize,
   duration: Duration,
 }
 
 impl Section {
-  pub fn new(notes: Vec<Note>, duration: Duration) -> Self {
+  pub fn new(
+    name: String,
+    notes: Vec<Note>,
+    duration: Duration,
+  ) -> Self {
     Self {
+      name,
       notes,
       current_note_index: 0,
       duration,
     }
   }
 
+  pub fn name(&self) -> &str {
+    &self.name
+  }
+
+  pub fn duration(&self) -> &Duration {
+    &self.duration
+  }
+
   pub fn current_note(&self) -> &Note {
     &self.notes[self.current_note_index]
   }
 
+  pub fn current_note_index(&self) -> usize {
+    self.current_note_index
+  }
+
   pub fn id(&self) -> NoteId {
     self.current_note().id()
   }
 
-  pub fn input(&mut self, typed: char) -> TypeResult {
-    self.notes[self.current_note_index].input(typed)
+  pub fn input(&mut self, typed: char, time: Seconds) -> TypeResult {
+    self.notes[self.current_note_index].input(typed, time)
+  }
+
+  // 文のノーツそれぞれの記録。section_index はこの節の位置
+  pub fn logs(
+    &self,
+    section_index: usize,
+    now: Seconds,
+  ) -> impl Iterator<Item = NoteLog> + '_ {
+    self
+      .notes
+      .iter()
+      .enumerate()
+      .filter_map(move |(index, note)| {
+        note.log((section_index, index), now)
+      })
   }
 
   pub fn accuracy(&self) -> f64 {
@@ -45,6 +78,7 @@ impl Section {
     }
   }
 
+  #[allow(clippy::needless_borrow)]
   pub fn update(&mut self, time: &Seconds) -> bool {
     for (index, note) in self.notes.iter().enumerate() {
       if note.duration().includes(&time) {
@@ -55,11 +89,37 @@ impl Section {
     false
   }
 
+  pub fn scale_time(&mut self, factor: f64) {
+    self.duration = self.duration.scaled(factor);
+    for note in &mut self.notes {
+      note.scale_time(factor);
+    }
+  }
+
+  // time より後に終わるノーツを、initial の同じノーツに戻す
+  pub fn reset_after(&mut self, time: &Seconds, initial: &Section) {
+    for (note, initial) in self.notes.iter_mut().zip(&initial.notes) {
+      if *time < note.duration().end() {
+        *note = initial.clone();
+      }
+    }
+  }
+
+  // 文と、それを書いた譜面の行番号
+  pub fn sentences_mut(
+    &mut self,
+  ) -> impl Iterator<Item = (usize, &mut Sentence)> {
+    self.notes.iter_mut().filter_map(|note| {
+      let line_num = note.line_num();
+      note.sentence_mut().map(|sentence| (line_num, sentence))
+    })
+  }
+
   pub fn iter(&self) -> impl Iterator<Item = &Note> {
     self.notes.iter()
   }
 
-  #[allow(dead_code)]
+  #[allow(dead_code, clippy::len_without_is_empty)]
   pub fn len(&self) -> usize {
     self.notes.len()
   }
@@ -4,6 +4,8 @@ use sentence::Sentence;
 mod scoring;
 pub mod sentence;
 
+pub use scoring::{Keystroke, NoteLog};
+
 #[derive(Debug)]
 pub enum TypeResult {
   Succeed,
@@ -26,9 +28,11 @@ pub struct Note {
   duration: Duration,
   content: NoteContent,
   scoring: Scoring,
+  // 譜面での行番号。譜面から読んでいなければ 0
+  line_num: usize,
 }
 
-use crate::model::exp::time::Duration;
+use crate::model::exp::time::{Duration, Seconds};
 use rand::distributions::Alphanumeric;
 use rand::{thread_rng, Rng};
 
@@ -44,9 +48,19 @@ impl Note {
       duration,
       content,
       scoring: Scoring::new(),
+      line_num: 0,
     }
   }
 
+  pub fn at_line(mut self, line_num: usize) -> Self {
+    self.line_num = line_num;
+    self
+  }
+
+  pub fn line_num(&self) -> usize {
+    self.line_num
+  }
+
   pub fn sentence(duration: Duration, sentence: Sentence) -> Self {
     Self::new(
       duration,
@@ -73,14 +87,22 @@ impl Note {
     &self.duration
   }
 
-  pub fn input(&mut self, typed: char) -> TypeResult {
+  pub fn scale_time(&mut self, factor: f64) {
+    self.duration = self.duration.scaled(factor);
+  }
+
+  // time は曲の始まりからの時間
+  pub fn input(&mut self, typed: char, time: Seconds) -> TypeResult {
     use NoteContent::Sentence;
     use TypeResult::*;
 
+    let (mut expected, mut kana) = (None, None);
     let res = if let Sentence {
       sentence, succeed, ..
     } = &mut self.content
     {
+      expected = sentence.roman().will_input.chars().next();
+      kana = sentence.inputting_kana();
       if sentence.completed() {
         Vacant
       } else if sentence.input(typed) {
@@ -93,9 +115,64 @@ impl Note {
       Vacant
     };
     self.scoring.point(&res);
+    if let (Sentence { sentence, .. }, Succeed | Mistaken) =
+      (&self.content, &res)
+    {
+      let roman = sentence.roman();
+      self.scoring.set_whole_type_count(
+        roman.inputted.chars().count()
+          + roman.will_input.chars().count(),
+      );
+      let keystroke = Keystroke {
+        time: time - self.duration.start(),
+        typed,
+        expected,
+        kana,
+        succeed: matches!(res, Succeed),
+      };
+      self.scoring.record(keystroke, sentence.completed());
+    }
     res
   }
 
+  // 文のノーツなら、now までにどう打ったかの記録
+  pub fn log(
+    &self,
+    position: (usize, usize),
+    now: Seconds,
+  ) -> Option<NoteLog> {
+    let sentence = match &self.content {
+      NoteContent::Sentence { sentence, .. } => sentence,
+      _ => return None,
+    };
+    let keystrokes = self.scoring.keystrokes();
+    let mut mistakes = std::collections::BTreeMap::new();
+    for keystroke in keystrokes.iter().filter(|k| !k.succeed) {
+      if let Some(expected) = keystroke.expected {
+        *mistakes.entry(expected).or_insert(0) += 1;
+      }
+    }
+    Some(NoteLog {
+      position,
+      origin: sentence.origin().into(),
+      start: self.duration.start(),
+      first_type: keystrokes.first().map(|keystroke| keystroke.time),
+      completed_at: self.scoring.completed_at(),
+      mistakes,
+      keystrokes: keystrokes.to_vec(),
+      accuracy: self.accuracy(),
+      achievement_rate: self.scoring.achievement_rate().as_f64(),
+      missed: !sentence.completed() && self.duration.end() <= now,
+    })
+  }
+
+  pub fn sentence_mut(&mut self) -> Option<&mut Sentence> {
+    match &mut self.content {
+      NoteContent::Sentence { sentence, .. } => Some(sentence),
+      _ => None,
+    }
+  }
+
   pub fn content(&self) -> &NoteContent {
     &self.content
   }
@@ -1,4 +1,6 @@
 use super::super::note::TypeResult;
+use crate::model::exp::time::Seconds;
+use std::collections::BTreeMap;
 
 #[derive(Debug, Default, Clone)]
 pub struct Accuracy {
@@ -36,10 +38,42 @@ pub struct AchievementRate {
   whole_type_count: u32,
 }
 
+impl AchievementRate {
+  fn point(&mut self, res: &TypeResult) {
+    if let TypeResult::Succeed = res {
+      self.correction_type_count += 1;
+    }
+  }
+
+  pub fn as_f64(&self) -> f64 {
+    if self.whole_type_count == 0 {
+      return 0.0;
+    }
+    (self.correction_type_count as f64 / self.whole_type_count as f64)
+      .min(1.0)
+  }
+}
+
+// 文を打っている間の 1 回の打鍵
+#[derive(Debug, Clone, PartialEq)]
+pub struct Keystroke {
+  // ノーツの始まりからの時間
+  pub time: Seconds,
+  pub typed: char,
+  // 表示している打ち方で次に打つはずだったキー
+  pub expected: Option<char>,
+  // 打っていた読み仮名
+  pub kana: Option<String>,
+  pub succeed: bool,
+}
+
 #[derive(Debug, Default, Clone)]
 pub struct Scoring {
   accuracy: Accuracy,
   achievement_rate: AchievementRate,
+  keystrokes: Vec<Keystroke>,
+  // 打ち終えた時間。ノーツの始まりから数える
+  completed_at: Option<Seconds>,
 }
 
 impl Scoring {
@@ -49,9 +83,53 @@ impl Scoring {
 
   pub fn point(&mut self, res: &TypeResult) {
     self.accuracy.point(res);
+    self.achievement_rate.point(res);
+  }
+
+  // 打った後の文の長さ。打ち方によって変わるので打つたびに合わせる
+  pub fn set_whole_type_count(&mut self, count: usize) {
+    self.achievement_rate.whole_type_count = count as u32;
+  }
+
+  pub fn record(&mut self, keystroke: Keystroke, completed: bool) {
+    if completed && self.completed_at.is_none() {
+      self.completed_at = Some(keystroke.time);
+    }
+    self.keystrokes.push(keystroke);
   }
 
   pub fn accuracy(&self) -> &Accuracy {
     &self.accuracy
   }
+
+  pub fn achievement_rate(&self) -> &AchievementRate {
+    &self.achievement_rate
+  }
+
+  pub fn keystrokes(&self) -> &[Keystroke] {
+    &self.keystrokes
+  }
+
+  pub fn completed_at(&self) -> Option<Seconds> {
+    self.completed_at
+  }
+}
+
+// 1 つの文をどう打ったかの記録
+#[derive(Debug, Clone, PartialEq)]
+pub struct NoteLog {
+  // 譜面の中での節と文の位置
+  pub position: (usize, usize),
+  pub origin: String,
+  // ノーツの始まる時間。以下の時間はここから数える
+  pub start: Seconds,
+  pub first_type: Option<Seconds>,
+  pub completed_at: Option<Seconds>,
+  // 打ち間違えた回数を、打つはずだったキーごとに数えたもの
+  pub mistakes: BTreeMap<char, u32>,
+  pub keystrokes: Vec<Keystroke>,
+  pub accuracy: f64,
+  pub achievement_rate: f64,
+  // 打ち終えないままノーツが終わった
+  pub missed: bool,
 }
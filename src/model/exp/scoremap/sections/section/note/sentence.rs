@@ -1,6 +1,10 @@
-use roman::{RomanParseError, RomanStr};
+use latin::{LatinOptions, LatinStr};
+use roman::{
+  InputScheme, RomanParseError, RomanStr, SpellingProfile,
+};
 use std::fmt::{Debug, Formatter};
 
+pub mod latin;
 pub mod roman;
 
 pub struct TypingStr {
@@ -8,15 +12,30 @@ pub struct TypingStr {
   pub inputted: String,
 }
 
+// 打つ対象
+#[derive(Debug, Clone, PartialEq)]
+enum Target {
+  Yomigana(RomanStr),
+  // 英語の歌詞などをそのまま打つ
+  Latin(LatinStr),
+}
+
 #[derive(Clone, PartialEq)]
 pub struct Sentence {
   origin: String,
-  hiragana: RomanStr,
+  target: Target,
 }
 
 impl Debug for Sentence {
   fn fmt(&self, mut f: &mut Formatter<'_>) -> std::fmt::Result {
-    write!(&mut f, "{} <-> {:?}", self.origin, self.hiragana)
+    match &self.target {
+      Target::Yomigana(hiragana) => {
+        write!(&mut f, "{} <-> {:?}", self.origin, hiragana)
+      }
+      Target::Latin(latin) => {
+        write!(&mut f, "{} <-> {:?}", self.origin, latin)
+      }
+    }
   }
 }
 
@@ -28,7 +47,7 @@ impl Sentence {
   ) -> Result<Self, RomanParseError> {
     Ok(Sentence {
       origin: origin.into(),
-      hiragana: RomanStr::new(to_input)?,
+      target: Target::Yomigana(RomanStr::new(to_input)?),
     })
   }
 
@@ -38,27 +57,83 @@ impl Sentence {
     to_input: &str,
     inputted: &str,
   ) -> Result<Self, RomanParseError> {
-    let mut roman_str = RomanStr::new(to_input)?;
+    let mut sentence = Self::new(origin, to_input)?;
     for inputted in inputted.chars() {
-      roman_str.input(inputted);
+      sentence.input(inputted);
     }
-    Ok(Sentence {
-      origin: origin.into(),
-      hiragana: roman_str,
-    })
+    Ok(sentence)
   }
 
   pub fn empty() -> Self {
     Sentence {
       origin: "".into(),
-      hiragana: RomanStr::new("").unwrap(),
+      target: Target::Yomigana(RomanStr::new("").unwrap()),
     }
   }
 
   pub fn from(origin: &str, yomigana: RomanStr) -> Self {
     Sentence {
       origin: origin.into(),
-      hiragana: yomigana,
+      target: Target::Yomigana(yomigana),
+    }
+  }
+
+  pub fn latin(origin: &str, to_input: &str) -> Self {
+    Sentence {
+      origin: origin.into(),
+      target: Target::Latin(LatinStr::new(to_input)),
+    }
+  }
+
+  // 読み仮名を別の入力方式で打てるようにする
+  pub fn set_input_scheme(
+    &mut self,
+    scheme: &InputScheme,
+  ) -> Result<(), RomanParseError> {
+    if let Target::Yomigana(hiragana) = &mut self.target {
+      *hiragana = RomanStr::with_scheme(hiragana.yomigana(), scheme)?;
+    }
+    Ok(())
+  }
+
+  // 末尾の「ん」を n 1 つで打ち終えられるようにする
+  pub fn accept_trailing_n(&mut self) {
+    if let Target::Yomigana(hiragana) = &mut self.target {
+      hiragana.accept_trailing_n();
+    }
+  }
+
+  // 表示するローマ字をよく使う打ち方に並べ替える
+  pub fn prefer_spellings(&mut self, profile: &SpellingProfile) {
+    if let Target::Yomigana(hiragana) = &mut self.target {
+      hiragana.prefer(profile);
+    }
+  }
+
+  // 今打っている読み仮名。英語の歌詞にはない
+  pub fn inputting_kana(&self) -> Option<String> {
+    match &self.target {
+      Target::Yomigana(hiragana) => {
+        hiragana.inputting_kana().map(Into::into)
+      }
+      Target::Latin(_) => None,
+    }
+  }
+
+  // 打ち終えた読み仮名と、実際に使った打ち方
+  pub fn spellings(&self) -> Vec<(String, String)> {
+    match &self.target {
+      Target::Yomigana(hiragana) => hiragana
+        .spellings()
+        .map(|(kana, style)| (kana.into(), style.into()))
+        .collect(),
+      Target::Latin(_) => vec![],
+    }
+  }
+
+  pub fn set_latin_options(&mut self, options: LatinOptions) {
+    if let Target::Latin(latin) = &mut self.target {
+      latin.set_options(options);
     }
   }
 
@@ -66,25 +141,63 @@ impl Sentence {
     self.origin.as_str()
   }
 
+  pub fn is_latin(&self) -> bool {
+    matches!(self.target, Target::Latin(_))
+  }
+
+  // 読み仮名、または英語の歌詞で打つ文字列
+  pub fn hiragana(&self) -> &str {
+    match &self.target {
+      Target::Yomigana(hiragana) => hiragana.yomigana(),
+      Target::Latin(latin) => latin.text(),
+    }
+  }
+
   pub fn yomiagana(&self) -> TypingStr {
-    TypingStr {
-      will_input: self.hiragana.will_input_yomigana().collect(),
-      inputted: self.hiragana.inputted_yomigana().collect(),
+    match &self.target {
+      Target::Yomigana(hiragana) => TypingStr {
+        will_input: hiragana.will_input_yomigana().collect(),
+        inputted: hiragana.inputted_yomigana().collect(),
+      },
+      Target::Latin(latin) => TypingStr {
+        will_input: latin.will_input(),
+        inputted: latin.inputted(),
+      },
     }
   }
 
   pub fn roman(&self) -> TypingStr {
-    TypingStr {
-      will_input: self.hiragana.will_input_roman(),
-      inputted: self.hiragana.inputted_roman().into(),
+    match &self.target {
+      Target::Yomigana(hiragana) => TypingStr {
+        will_input: hiragana.will_input_roman(),
+        inputted: hiragana.inputted_roman().into(),
+      },
+      Target::Latin(latin) => TypingStr {
+        will_input: latin.will_input(),
+        inputted: latin.inputted(),
+      },
+    }
+  }
+
+  // 次に打てるキー全て。表示している打ち方のキーが先
+  pub fn next_keys(&self) -> Vec<char> {
+    match &self.target {
+      Target::Yomigana(hiragana) => hiragana.next_keys(),
+      Target::Latin(latin) => latin.next_keys(),
     }
   }
 
   pub fn input(&mut self, typed: char) -> bool {
-    self.hiragana.input(typed)
+    match &mut self.target {
+      Target::Yomigana(hiragana) => hiragana.input(typed),
+      Target::Latin(latin) => latin.input(typed),
+    }
   }
 
   pub fn completed(&self) -> bool {
-    self.hiragana.completed()
+    match &self.target {
+      Target::Yomigana(hiragana) => hiragana.completed(),
+      Target::Latin(latin) => latin.completed(),
+    }
   }
 }
@@ -0,0 +1,156 @@
+use std::fmt::{Debug, Formatter};
+
+// 英語の歌詞をそのまま打つときの判定の仕方
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct LatinOptions {
+  pub case_sensitive: bool,
+  pub strict_punctuation: bool,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct LatinStr {
+  text: String,
+  chars: Vec<char>,
+  inputting_char: usize,
+  options: LatinOptions,
+}
+
+impl Debug for LatinStr {
+  fn fmt(&self, mut f: &mut Formatter<'_>) -> std::fmt::Result {
+    write!(
+      &mut f,
+      "{:?}({})",
+      self.text,
+      self.chars[..self.inputting_char].iter().collect::<String>()
+    )
+  }
+}
+
+// 英数字と空白以外は記号とみなす
+fn is_punctuation(c: char) -> bool {
+  !c.is_alphanumeric() && !c.is_whitespace()
+}
+
+impl LatinStr {
+  pub fn new(text: &str) -> Self {
+    // 空白の連なりは 1 つの空白として打つ
+    let text = text.split_whitespace().collect::<Vec<_>>().join(" ");
+    Self {
+      chars: text.chars().collect(),
+      text,
+      inputting_char: 0,
+      options: LatinOptions::default(),
+    }
+  }
+
+  pub fn set_options(&mut self, options: LatinOptions) {
+    self.options = options;
+    self.skip_trailing_punctuation();
+  }
+
+  pub fn text(&self) -> &str {
+    &self.text
+  }
+
+  pub fn will_input(&self) -> String {
+    self.chars[self.inputting_char..].iter().collect()
+  }
+
+  pub fn inputted(&self) -> String {
+    self.chars[..self.inputting_char].iter().collect()
+  }
+
+  // 次に打てるキー全て。読み飛ばせる記号も含む
+  pub fn next_keys(&self) -> Vec<char> {
+    let mut keys = vec![];
+    for &expected in &self.chars[self.inputting_char..] {
+      if !keys.contains(&expected) {
+        keys.push(expected);
+      }
+      let lower = expected.to_ascii_lowercase();
+      if !self.options.case_sensitive && !keys.contains(&lower) {
+        keys.push(lower);
+      }
+      if self.options.strict_punctuation || !is_punctuation(expected)
+      {
+        break;
+      }
+    }
+    keys
+  }
+
+  fn matches(&self, expected: char, typed: char) -> bool {
+    if self.options.case_sensitive {
+      expected == typed
+    } else {
+      expected.to_lowercase().eq(typed.to_lowercase())
+    }
+  }
+
+  pub fn input(&mut self, typed: char) -> bool {
+    let skippable = !self.options.strict_punctuation;
+    let mut next = self.inputting_char;
+    while let Some(&expected) = self.chars.get(next) {
+      if self.matches(expected, typed) {
+        self.inputting_char = next + 1;
+        self.skip_trailing_punctuation();
+        return true;
+      }
+      if !(skippable && is_punctuation(expected)) {
+        break;
+      }
+      next += 1;
+    }
+    false
+  }
+
+  // 記号を打たなくてよいなら、末尾の記号だけが残った時点で打ち終わる
+  fn skip_trailing_punctuation(&mut self) {
+    if self.options.strict_punctuation || self.inputting_char == 0 {
+      return;
+    }
+    if self.chars[self.inputting_char..]
+      .iter()
+      .all(|&c| is_punctuation(c))
+    {
+      self.inputting_char = self.chars.len();
+    }
+  }
+
+  pub fn completed(&self) -> bool {
+    self.chars.len() <= self.inputting_char
+  }
+}
+
+#[test]
+fn lenient() {
+  let mut hello = LatinStr::new("Hello,  world!");
+  assert_eq!(hello.text(), "Hello, world!");
+  for typed in "hello world".chars() {
+    assert!(hello.input(typed), "{}", typed);
+  }
+  assert!(hello.completed());
+  assert_eq!(hello.inputted(), "Hello, world!");
+
+  let mut quote = LatinStr::new("\"Hi\"");
+  assert_eq!(quote.next_keys(), ['"', 'H', 'h']);
+  assert!(quote.input('h'));
+  assert_eq!(quote.next_keys(), ['i']);
+}
+
+#[test]
+fn strict() {
+  let mut hello = LatinStr::new("Don't stop");
+  hello.set_options(LatinOptions {
+    case_sensitive: true,
+    strict_punctuation: true,
+  });
+  assert!(!hello.input('d'));
+  for typed in "Don".chars() {
+    assert!(hello.input(typed), "{}", typed);
+  }
+  assert!(!hello.input('t'));
+  assert!(hello.input('\''));
+  assert_eq!(hello.will_input(), "t stop");
+  assert_eq!(hello.next_keys(), ['t']);
+}
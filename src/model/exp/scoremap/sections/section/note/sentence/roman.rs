@@ -1,7 +1,15 @@
+mod input_scheme;
 mod roman_char;
 mod roman_lexer;
 mod roman_str;
+mod roman_table;
+mod spelling_profile;
+#[cfg(test)]
+mod verification;
 
+pub use self::input_scheme::*;
 pub use self::roman_char::*;
 pub use self::roman_lexer::*;
 pub use self::roman_str::*;
+pub use self::roman_table::*;
+pub use self::spelling_profile::*;
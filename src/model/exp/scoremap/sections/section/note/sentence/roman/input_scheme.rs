@@ -0,0 +1,133 @@
+use super::{RomanTable, RomanTableError};
+use std::sync::OnceLock;
+
+static AZIK_TABLE: OnceLock<RomanTable> = OnceLock::new();
+static ACT_TABLE: OnceLock<RomanTable> = OnceLock::new();
+static JIS_KANA_TABLE: OnceLock<RomanTable> = OnceLock::new();
+
+// 読み仮名を打つ方式
+#[derive(Debug, Clone, Default)]
+pub enum InputScheme {
+  #[default]
+  Standard,
+  Azik,
+  Act,
+  // 仮名を直接打つ
+  JisKana,
+  Custom(RomanTable),
+}
+
+impl InputScheme {
+  // 標準の表に書き足す形の表から独自の方式を作る
+  pub fn custom(source: &str) -> Result<Self, RomanTableError> {
+    Ok(InputScheme::Custom(RomanTable::current().overlay(source)?))
+  }
+
+  // 起動時の引数やリプレイに書く名前
+  pub fn from_name(name: &str) -> Option<Self> {
+    use InputScheme::*;
+    match name {
+      "standard" => Some(Standard),
+      "azik" => Some(Azik),
+      "act" => Some(Act),
+      "kana" => Some(JisKana),
+      _ => None,
+    }
+  }
+
+  pub fn name(&self) -> &'static str {
+    use InputScheme::*;
+    match self {
+      Standard => "standard",
+      Azik => "azik",
+      Act => "act",
+      JisKana => "kana",
+      Custom(_) => "custom",
+    }
+  }
+
+  pub fn is_kana(&self) -> bool {
+    matches!(self, InputScheme::JisKana)
+  }
+
+  pub fn table(&self) -> &RomanTable {
+    use InputScheme::*;
+    let overlaid = |table: &'static OnceLock<RomanTable>, source| {
+      table.get_or_init(|| {
+        RomanTable::current()
+          .overlay(source)
+          .expect("bundled input scheme is invalid")
+      })
+    };
+    match self {
+      Standard => RomanTable::current(),
+      Azik => overlaid(&AZIK_TABLE, include_str!("azik.tsv")),
+      Act => overlaid(&ACT_TABLE, include_str!("act.tsv")),
+      JisKana => JIS_KANA_TABLE.get_or_init(|| {
+        RomanTable::parse(include_str!("jis_kana.tsv"))
+          .expect("bundled input scheme is invalid")
+      }),
+      Custom(table) => table,
+    }
+  }
+}
+
+#[test]
+fn azik() -> Result<(), super::RomanParseError> {
+  use super::RomanStr;
+  let mut kantan =
+    RomanStr::with_scheme("かんたんな", &InputScheme::Azik)?;
+  assert_eq!(kantan.will_input_roman(), "kantannna");
+  for typed in "kztzna".chars() {
+    assert!(kantan.input(typed), "{}", typed);
+  }
+  assert!(kantan.completed());
+
+  let mut kantan =
+    RomanStr::with_scheme("かんたんな", &InputScheme::Azik)?;
+  for typed in "kantannna".chars() {
+    assert!(kantan.input(typed), "{}", typed);
+  }
+  assert!(kantan.completed());
+
+  let mut gakkou =
+    RomanStr::with_scheme("がっこう", &InputScheme::Azik)?;
+  for typed in "ga;kp".chars() {
+    assert!(gakkou.input(typed), "{}", typed);
+  }
+  assert!(gakkou.completed());
+  Ok(())
+}
+
+#[test]
+fn custom() {
+  let scheme = InputScheme::custom("とうきょう\ttk\n").unwrap();
+  let mut tokyo =
+    super::RomanStr::with_scheme("とうきょう", &scheme).unwrap();
+  assert!(tokyo.input('t'));
+  assert!(tokyo.input('k'));
+  assert!(tokyo.completed());
+  assert_eq!(tokyo.inputted_yomigana().count(), 5);
+
+  assert!(matches!(
+    InputScheme::custom("かい\tka\n"),
+    Err(RomanTableError::AmbiguousStyles { line_num: 1, .. })
+  ));
+}
+
+#[test]
+fn jis_kana() -> Result<(), super::RomanParseError> {
+  let mut gakkou =
+    super::RomanStr::with_scheme("ガッコウ", &InputScheme::JisKana)?;
+  assert_eq!(gakkou.will_input_roman(), "か゛っこう");
+  for typed in "か゛っ".chars() {
+    assert!(gakkou.input(typed), "{}", typed);
+  }
+  assert!(!gakkou.input('k'));
+  assert_eq!(gakkou.inputted_yomigana().collect::<String>(), "ガッ");
+  for typed in "こう".chars() {
+    assert!(gakkou.input(typed), "{}", typed);
+  }
+  assert!(gakkou.completed());
+  Ok(())
+}
@@ -1,10 +1,13 @@
+use super::SpellingProfile;
 use std::fmt::{Debug, Formatter, Result};
 
 #[derive(Clone, PartialEq)]
 pub struct RomanChar {
-  styles: Vec<&'static str>,
-  determined_style: Option<&'static str>,
+  styles: Vec<String>,
+  determined_style: Option<usize>,
   inputted: String,
+  // この文字で打つ読み仮名
+  kana: String,
 }
 
 impl Debug for RomanChar {
@@ -14,33 +17,79 @@ impl Debug for RomanChar {
 }
 
 impl RomanChar {
-  pub fn new(styles: &[&'static str]) -> Self {
+  pub fn new(styles: &[&str]) -> Self {
+    Self::from_styles(styles.iter().map(|&s| s.into()).collect(), "")
+  }
+
+  pub fn from_styles(styles: Vec<String>, kana: &str) -> Self {
     Self {
-      styles: Vec::from(styles),
+      styles,
       determined_style: None,
       inputted: String::new(),
+      kana: kana.into(),
+    }
+  }
+
+  pub fn kana(&self) -> &str {
+    &self.kana
+  }
+
+  pub fn kana_len(&self) -> usize {
+    self.kana.chars().count()
+  }
+
+  // よく使う打ち方を先に並べる
+  pub fn prefer(&mut self, profile: &SpellingProfile) {
+    let kana = &self.kana;
+    self.styles.sort_by_key(|style| {
+      std::cmp::Reverse(profile.rank(kana, style))
+    });
+  }
+
+  // 続く仮名がなければ n 1 つで打ち終える
+  pub fn accept_single_n(&mut self) {
+    if self.styles.iter().any(|style| style == "nn") {
+      self.styles.retain(|style| style != "nn");
+      self.styles.insert(0, "n".into());
     }
   }
 
-  fn determine(&mut self, input: &str) -> Option<&'static str> {
-    let filtered: Vec<_> = self
+  fn determine(&mut self, input: &str) -> Option<usize> {
+    self
       .styles
       .iter()
-      .filter(|style| style.starts_with(input))
-      .collect();
-    if filtered.is_empty() {
-      None
-    } else {
-      Some(filtered[0])
-    }
+      .position(|style| style.starts_with(input))
   }
 
-  pub fn styles(&self) -> &[&str] {
+  pub fn styles(&self) -> &[String] {
     &self.styles
   }
 
+  pub fn inputted(&self) -> &str {
+    &self.inputted
+  }
+
   pub fn determined_style(&self) -> &str {
-    self.determined_style.unwrap_or_else(|| self.styles()[0])
+    &self.styles[self.determined_style.unwrap_or(0)]
+  }
+
+  // 次に打てるキー。表示している打ち方のものを先にする
+  pub fn next_keys(&self) -> Vec<char> {
+    let inputted = self.inputted.as_str();
+    let mut keys = vec![];
+    for style in std::iter::once(self.determined_style())
+      .chain(self.styles.iter().map(String::as_str))
+    {
+      if !style.starts_with(inputted) {
+        continue;
+      }
+      if let Some(key) = style[inputted.len()..].chars().next() {
+        if !keys.contains(&key) {
+          keys.push(key);
+        }
+      }
+    }
+    keys
   }
 
   pub fn input(&mut self, typed: char) -> bool {
@@ -55,18 +104,53 @@ impl RomanChar {
     }
   }
 
+  // 打った分を延ばした打ち方がまだあれば、次の文字を打つまで確定しない
   pub fn completed_input(&self) -> bool {
     self.determined_style().len() == self.inputted.len()
+      && !self.styles.iter().any(|style| {
+        self.inputted.len() < style.len()
+          && style.starts_with(self.inputted.as_str())
+      })
+  }
+
+  // 打った分がそのまま打ち方になっていれば、それに確定する
+  pub fn settle(&mut self) -> bool {
+    if self.inputted.is_empty() {
+      return false;
+    }
+    match self.styles.iter().position(|style| *style == self.inputted)
+    {
+      Some(index) => {
+        self.styles = vec![self.styles.remove(index)];
+        self.determined_style = Some(0);
+        true
+      }
+      None => false,
+    }
+  }
+
+  // 促音を子音 1 つで打ったなら、その子音。次の文字はこれで始める
+  pub fn doubled_consonant(&self) -> Option<char> {
+    let mut style = self.determined_style().chars();
+    match (style.next(), style.next()) {
+      (Some(consonant), None) if self.kana == "っ" => {
+        Some(consonant)
+      }
+      _ => None,
+    }
   }
 
   pub fn fix_style(&mut self, typed: char) {
     let fixed: Vec<_> = self
       .styles
       .iter()
-      .cloned()
       .filter(|s| s.starts_with(typed))
+      .cloned()
       .collect();
     if !fixed.is_empty() {
+      let determined = self.determined_style().to_owned();
+      self.determined_style =
+        fixed.iter().position(|style| *style == determined);
       self.styles = fixed;
     }
   }
@@ -86,3 +170,25 @@ fn tea() {
   assert!(tea.input('t'));
   assert_eq!("tya", tea.determined_style());
 }
+
+#[test]
+fn next_keys() {
+  let mut tea = RomanChar::new(&["tya", "cha", "cya"]);
+  assert_eq!(tea.next_keys(), ['t', 'c']);
+  assert!(tea.input('c'));
+  assert_eq!(tea.next_keys(), ['h', 'y']);
+}
+
+#[test]
+fn single_n() {
+  let mut n = RomanChar::new(&["n", "nn", "xn"]);
+  assert!(n.input('n'));
+  assert!(!n.completed_input());
+  assert!(n.settle());
+  assert!(n.completed_input());
+  assert_eq!(n.styles(), ["n"]);
+
+  let mut n = RomanChar::new(&["nn", "xn"]);
+  assert!(n.input('n'));
+  assert!(!n.settle());
+}
@@ -1,4 +1,7 @@
-use super::{parse, RomanChar, RomanParseError};
+use super::{
+  parse_with, InputScheme, RomanChar, RomanParseError,
+  SpellingProfile,
+};
 use std::fmt::{Debug, Formatter};
 
 #[derive(Clone, PartialEq)]
@@ -26,9 +29,16 @@ impl Debug for RomanStr {
 
 impl RomanStr {
   pub fn new(yomigana: &str) -> Result<Self, RomanParseError> {
+    Self::with_scheme(yomigana, &InputScheme::Standard)
+  }
+
+  pub fn with_scheme(
+    yomigana: &str,
+    scheme: &InputScheme,
+  ) -> Result<Self, RomanParseError> {
     let chars: Vec<char> = yomigana.chars().collect();
     let mut parsed: Vec<RomanChar> = vec![];
-    parse(&mut parsed, chars.as_slice())?;
+    parse_with(scheme.table(), &mut parsed, chars.as_slice())?;
     Ok(RomanStr {
       yomigana: yomigana.into(),
       chars: parsed,
@@ -37,12 +47,17 @@ impl RomanStr {
     })
   }
 
+  pub fn yomigana(&self) -> &str {
+    self.yomigana.as_str()
+  }
+
   fn inputted_yomigana_index(&self) -> usize {
     self
       .chars
       .iter()
       .take_while(|c| c.completed_input())
-      .count()
+      .map(|c| c.kana_len())
+      .sum()
   }
 
   pub fn will_input_yomigana(
@@ -75,17 +90,91 @@ impl RomanStr {
     if self.chars[self.inputting_char].input(typed) {
       self.inputted.push(typed);
       if self.chars[self.inputting_char].completed_input() {
-        self.inputting_char += 1;
+        self.advance();
+      }
+      return true;
+    }
+    // 「ん」の n のように、次の文字が打てて初めて確定する打ち方
+    if self.inputting_char + 1 < self.chars.len() {
+      let pending = self.chars.clone();
+      if self.chars[self.inputting_char].settle() {
+        self.advance();
+        if self.input(typed) {
+          return true;
+        }
+        self.inputting_char -= 1;
+        self.chars = pending;
+      }
+    }
+    false
+  }
+
+  fn advance(&mut self) {
+    let completed = &self.chars[self.inputting_char];
+    let doubled = completed
+      .doubled_consonant()
+      .filter(|_| self.inputting_char + 1 < self.chars.len());
+    self.inputting_char += 1;
+    if let Some(consonant) = doubled {
+      self.chars[self.inputting_char].fix_style(consonant);
+    }
+  }
+
+  // 次に打てるキー全て
+  pub fn next_keys(&self) -> Vec<char> {
+    let current = match self.chars.get(self.inputting_char) {
+      Some(current) => current,
+      None => return vec![],
+    };
+    let mut keys = current.next_keys();
+    // 確定を待っている打ち方なら、次の文字の最初のキーも打てる
+    let mut settled = self.clone();
+    if self.inputting_char + 1 < self.chars.len()
+      && settled.chars[self.inputting_char].settle()
+    {
+      settled.advance();
+      let mut following = settled.next_keys();
+      following.retain(|key| !keys.contains(key));
+      // 表示している打ち方を打ち終えていれば、次の文字のキーが先
+      if current.determined_style() == current.inputted() {
+        following.append(&mut keys);
+        keys = following;
+      } else {
+        keys.append(&mut following);
       }
-      if !self.completed() {
-        self.chars[self.inputting_char].fix_style(typed);
+    }
+    keys
+  }
+
+  // 末尾の「ん」を n 1 つで打ち終えられるようにする
+  pub fn accept_trailing_n(&mut self) {
+    if let Some(last) = self.chars.last_mut() {
+      if last.kana() == "ん" {
+        last.accept_single_n();
       }
-      true
-    } else {
-      false
     }
   }
 
+  pub fn prefer(&mut self, profile: &SpellingProfile) {
+    for roman_char in self.chars.iter_mut() {
+      roman_char.prefer(profile);
+    }
+  }
+
+  // 今打っている文字の読み仮名
+  pub fn inputting_kana(&self) -> Option<&str> {
+    self.chars.get(self.inputting_char).map(RomanChar::kana)
+  }
+
+  // 打ち終えた文字の読み仮名と、実際に使った打ち方
+  pub fn spellings(&self) -> impl Iterator<Item = (&str, &str)> {
+    self
+      .chars
+      .iter()
+      .take_while(|c| c.completed_input())
+      .map(|c| (c.kana(), c.determined_style()))
+  }
+
   pub fn completed(&self) -> bool {
     self.chars.len() <= self.inputting_char
   }
@@ -129,6 +218,63 @@ fn hello() -> Result<(), RomanParseError> {
   Ok(())
 }
 
+#[test]
+fn single_n() -> Result<(), RomanParseError> {
+  let mut kanji = RomanStr::new("かんじ")?;
+  assert_eq!(kanji.will_input_roman(), "kanzi");
+  for typed in "kan".chars() {
+    assert!(kanji.input(typed), "{}", typed);
+  }
+  assert_eq!(kanji.inputted_yomigana().collect::<String>(), "か");
+  assert!(!kanji.input('a'));
+  assert!(kanji.input('j'));
+  assert_eq!(kanji.inputted_yomigana().collect::<String>(), "かん");
+  assert!(kanji.input('i'));
+  assert!(kanji.completed());
+
+  let mut kanji = RomanStr::new("かんじ")?;
+  for typed in "kannzi".chars() {
+    assert!(kanji.input(typed), "{}", typed);
+  }
+  assert!(kanji.completed());
+
+  let mut kanna = RomanStr::new("かんな")?;
+  assert_eq!(kanna.will_input_roman(), "kannna");
+  for typed in "kan".chars() {
+    assert!(kanna.input(typed), "{}", typed);
+  }
+  assert!(kanna.input('n'));
+  assert_eq!(kanna.inputted_yomigana().collect::<String>(), "かん");
+
+  let mut hon = RomanStr::new("ほん")?;
+  assert_eq!(hon.will_input_roman(), "honn");
+  assert!(hon.input('h') && hon.input('o') && hon.input('n'));
+  assert!(!hon.completed());
+  let mut hon = RomanStr::new("ほん")?;
+  hon.accept_trailing_n();
+  assert_eq!(hon.will_input_roman(), "hon");
+  assert!(hon.input('h') && hon.input('o') && hon.input('n'));
+  assert!(hon.completed());
+  Ok(())
+}
+
+#[test]
+fn next_keys() -> Result<(), RomanParseError> {
+  let mut kanji = RomanStr::new("かんじ")?;
+  assert_eq!(kanji.next_keys(), ['k']);
+  for typed in "ka".chars() {
+    assert!(kanji.input(typed));
+  }
+  assert_eq!(kanji.next_keys(), ['n', 'x']);
+  assert!(kanji.input('n'));
+  assert_eq!(kanji.next_keys(), ['z', 'j', 'n']);
+  for typed in "ji".chars() {
+    assert!(kanji.input(typed));
+  }
+  assert!(kanji.next_keys().is_empty());
+  Ok(())
+}
+
 #[test]
 fn toy() -> Result<(), RomanParseError> {
   let mut hello = RomanStr::new("おもちゃ")?;
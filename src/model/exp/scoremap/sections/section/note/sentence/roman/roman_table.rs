@@ -0,0 +1,536 @@
+use std::{
+  collections::HashMap,
+  fmt::{Display, Formatter, Result},
+  sync::OnceLock,
+};
+
+const BUNDLED: &str = include_str!("romaji.tsv");
+
+static BUNDLED_TABLE: OnceLock<RomanTable> = OnceLock::new();
+static USER_TABLE: OnceLock<RomanTable> = OnceLock::new();
+
+const SMALL_KANA: &[char] = &[
+  'ぁ', 'ぃ', 'ぅ', 'ぇ', 'ぉ', 'っ', 'ゃ', 'ゅ', 'ょ', 'ゎ', 'ゕ',
+  'ゖ',
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum RomanTableError {
+  InvalidRule {
+    line_num: usize,
+    reason: &'static str,
+  },
+  DuplicatedKana {
+    line_num: usize,
+    first_line_num: usize,
+    kana: String,
+  },
+  AmbiguousStyles {
+    line_num: usize,
+    shorter: String,
+    longer: String,
+  },
+}
+
+impl Display for RomanTableError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    use RomanTableError::*;
+    match self {
+      InvalidRule { line_num, reason } => {
+        write!(f, "{} 行目: {}", line_num, reason)
+      }
+      DuplicatedKana {
+        line_num,
+        first_line_num,
+        kana,
+      } => write!(
+        f,
+        "{} 行目: `{}` は {} 行目で定義済みです。",
+        line_num, kana, first_line_num
+      ),
+      AmbiguousStyles {
+        line_num,
+        shorter,
+        longer,
+      } => write!(
+        f,
+        "{} 行目: `{}` を打った時点で確定するため `{}` が打てません。",
+        line_num, shorter, longer
+      ),
+    }
+  }
+}
+
+#[derive(Debug, Default, Clone)]
+struct TrieNode {
+  children: HashMap<char, TrieNode>,
+  styles: Option<Vec<String>>,
+  // 複数の仮名をまとめて打つ省略入力
+  shortcut: bool,
+  line_num: usize,
+}
+
+// 仮名の並びから打ち方を引く木
+#[derive(Debug, Default, Clone)]
+pub struct RomanTable {
+  root: TrieNode,
+}
+
+type Rule<'a> = (usize, &'a str, Vec<String>);
+
+fn check_ambiguity(
+  line_num: usize,
+  styles: &[String],
+) -> std::result::Result<(), RomanTableError> {
+  // 先に確定してしまう打ち方は曖昧
+  for (index, shorter) in styles.iter().enumerate() {
+    if let Some(longer) =
+      styles.iter().skip(index + 1).find(|longer| {
+        longer.starts_with(shorter.as_str())
+          || shorter.starts_with(longer.as_str())
+      })
+    {
+      let (shorter, longer) = if shorter.len() <= longer.len() {
+        (shorter, longer)
+      } else {
+        (longer, shorter)
+      };
+      return Err(RomanTableError::AmbiguousStyles {
+        line_num,
+        shorter: shorter.clone(),
+        longer: longer.clone(),
+      });
+    }
+  }
+  Ok(())
+}
+
+// 仮名の並びが前にある行の打ち方と、後に続く行の打ち方も比べる
+fn check_nested(
+  node: &TrieNode,
+  ancestors: &mut Vec<(usize, Vec<String>)>,
+) -> std::result::Result<(), RomanTableError> {
+  let styles = node.styles.as_deref().unwrap_or_default();
+  for (line_num, ancestor_styles) in ancestors.iter() {
+    for ancestor in ancestor_styles {
+      if let Some(style) = styles.iter().find(|style| {
+        style.starts_with(ancestor.as_str())
+          || ancestor.starts_with(style.as_str())
+      }) {
+        let (shorter, longer) = if ancestor.len() <= style.len() {
+          (ancestor, style)
+        } else {
+          (style, ancestor)
+        };
+        return Err(RomanTableError::AmbiguousStyles {
+          line_num: node.line_num.max(*line_num),
+          shorter: shorter.clone(),
+          longer: longer.clone(),
+        });
+      }
+    }
+  }
+  if !styles.is_empty() {
+    ancestors.push((node.line_num, styles.to_vec()));
+  }
+  for child in node.children.values() {
+    check_nested(child, ancestors)?;
+  }
+  if !styles.is_empty() {
+    ancestors.pop();
+  }
+  Ok(())
+}
+
+fn rules(
+  source: &str,
+) -> std::result::Result<Vec<Rule<'_>>, RomanTableError> {
+  use RomanTableError::*;
+  let mut rules = vec![];
+  for (line_num, line) in source.lines().enumerate() {
+    let line_num = line_num + 1;
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+      continue;
+    }
+    let mut columns = line.split_whitespace();
+    let kana = columns.next().unwrap_or_default();
+    let styles: Vec<String> = columns.map(Into::into).collect();
+    if styles.is_empty() {
+      return Err(InvalidRule {
+        line_num,
+        reason: "打ち方がありません。",
+      });
+    }
+    if styles
+      .iter()
+      .any(|style| style.chars().any(char::is_control))
+    {
+      return Err(InvalidRule {
+        line_num,
+        reason: "打ち方に制御文字は使えません。",
+      });
+    }
+    check_ambiguity(line_num, &styles)?;
+    rules.push((line_num, kana, styles));
+  }
+  Ok(rules)
+}
+
+impl RomanTable {
+  pub fn parse(
+    source: &str,
+  ) -> std::result::Result<Self, RomanTableError> {
+    let mut table = Self::default();
+    for (line_num, kana, styles) in rules(source)? {
+      let node = table.node_mut(kana);
+      if node.styles.is_some() {
+        return Err(RomanTableError::DuplicatedKana {
+          line_num,
+          first_line_num: node.line_num,
+          kana: kana.into(),
+        });
+      }
+      node.styles = Some(styles);
+      node.line_num = line_num;
+    }
+    check_nested(&table.root, &mut vec![])?;
+    Ok(table)
+  }
+
+  // この表に打ち方を書き足した表を作る
+  // 表にない複数の仮名への打ち方は、普通の打ち方と並ぶ省略入力になる
+  pub fn overlay(
+    &self,
+    source: &str,
+  ) -> std::result::Result<Self, RomanTableError> {
+    let mut table = self.clone();
+    for (line_num, kana, styles) in rules(source)? {
+      let chars: Vec<char> = kana.chars().collect();
+      let spelled = match self.lookup(&chars) {
+        Some((len, node)) if len == chars.len() && !node.shortcut => {
+          None
+        }
+        _ if chars.len() == 1 => None,
+        _ => Some(self.spell(&chars, chars.len()).ok_or(
+          RomanTableError::InvalidRule {
+            line_num,
+            reason: "省略入力の仮名がこの表では打てません。",
+          },
+        )?),
+      };
+      let node = table.node_mut(kana);
+      let mut merged = node.styles.take().unwrap_or_default();
+      merged.extend(styles);
+      if let Some(spelled) = spelled {
+        node.shortcut = true;
+        let mut all = spelled;
+        all.extend(merged.iter().cloned());
+        check_ambiguity(line_num, &all)?;
+      } else {
+        check_ambiguity(line_num, &merged)?;
+      }
+      node.styles = Some(merged);
+      node.line_num = line_num;
+    }
+    check_nested(&table.root, &mut vec![])?;
+    Ok(table)
+  }
+
+  fn node_mut(&mut self, kana: &str) -> &mut TrieNode {
+    kana.chars().fold(&mut self.root, |node, c| {
+      node.children.entry(c).or_default()
+    })
+  }
+
+  pub fn bundled() -> &'static Self {
+    BUNDLED_TABLE.get_or_init(|| {
+      Self::parse(BUNDLED).expect("bundled romaji table is invalid")
+    })
+  }
+
+  // 読み込んだ表を以後の既定にする
+  pub fn install(self) -> std::result::Result<(), Self> {
+    USER_TABLE.set(self)
+  }
+
+  pub fn current() -> &'static Self {
+    USER_TABLE.get().unwrap_or_else(Self::bundled)
+  }
+
+  // 仮名と打ち方を並べ替えて書き出したもの。打ち方が同じ表なら行の順番によらず同じになる
+  pub fn canonical_text(&self) -> String {
+    fn walk(
+      node: &TrieNode,
+      kana: &mut String,
+      lines: &mut Vec<String>,
+    ) {
+      if let Some(styles) = &node.styles {
+        let mut styles = styles.clone();
+        styles.sort();
+        lines.push(format!(
+          "{}	{}	{}",
+          kana,
+          styles.join(" "),
+          node.shortcut
+        ));
+      }
+      for (&c, child) in &node.children {
+        kana.push(c);
+        walk(child, kana, lines);
+        kana.pop();
+      }
+    }
+    let mut lines = vec![];
+    walk(&self.root, &mut String::new(), &mut lines);
+    lines.sort();
+    lines
+      .iter()
+      .map(|line| {
+        format!(
+          "{}
+",
+          line
+        )
+      })
+      .collect()
+  }
+
+  pub fn contains(&self, kana: char) -> bool {
+    self.root.children.contains_key(&kana)
+  }
+
+  fn lookup(&self, kana: &[char]) -> Option<(usize, &TrieNode)> {
+    let mut node = &self.root;
+    let mut found = None;
+    for (index, c) in kana.iter().enumerate() {
+      node = match node.children.get(c) {
+        Some(child) => child,
+        None => break,
+      };
+      if node.styles.is_some() {
+        found = Some((index + 1, node));
+      }
+    }
+    found
+  }
+
+  // 最も長く一致する仮名の文字数と打ち方
+  pub fn longest_match(
+    &self,
+    kana: &[char],
+  ) -> Option<(usize, &[String])> {
+    let mut node = &self.root;
+    let mut found = None;
+    for (index, c) in kana.iter().enumerate() {
+      node = match node.children.get(c) {
+        Some(child) => child,
+        None => break,
+      };
+      match &node.styles {
+        Some(styles) if !node.shortcut => {
+          found = Some((index + 1, styles.as_slice()))
+        }
+        _ => {}
+      }
+    }
+    found
+  }
+
+  // 次の 1 文字分として打つ仮名の数と打ち方
+  pub fn next_char(
+    &self,
+    kana: &[char],
+  ) -> Option<(usize, Vec<String>)> {
+    if let Some((len, node)) = self.lookup(kana) {
+      if node.shortcut {
+        if let Some(mut styles) = self.spell(kana, len) {
+          for style in node.styles.iter().flatten() {
+            if !styles.contains(style) {
+              styles.push(style.clone());
+            }
+          }
+          return Some((len, styles));
+        }
+      }
+    }
+    self.standard_char(kana)
+  }
+
+  fn standard_char(
+    &self,
+    kana: &[char],
+  ) -> Option<(usize, Vec<String>)> {
+    if let Some(styles) = self.contextual(kana) {
+      return Some((1, styles));
+    }
+    self
+      .longest_match(kana)
+      .map(|(len, styles)| (len, styles.to_vec()))
+  }
+
+  // 先頭 len 文字の仮名を、省略入力を使わずに打つ打ち方全て
+  fn spell(&self, kana: &[char], len: usize) -> Option<Vec<String>> {
+    let mut spelled = vec![String::new()];
+    let mut position = 0;
+    while position < len {
+      let (count, styles) = self.standard_char(&kana[position..])?;
+      position += count;
+      spelled = spelled
+        .iter()
+        .flat_map(|prefix| {
+          styles.iter().map(move |style| prefix.clone() + style)
+        })
+        .collect();
+    }
+    if position == len {
+      Some(spelled)
+    } else {
+      None
+    }
+  }
+
+  // 前後の仮名で打ち方が変わるもの
+  pub fn contextual(&self, kana: &[char]) -> Option<Vec<String>> {
+    let (first, rest) = kana.split_first()?;
+    let next = rest.first()?;
+    let (_, next_styles) = self.longest_match(rest)?;
+    let (_, own_styles) = self.longest_match(&kana[..1])?;
+    match first {
+      // 促音は次の仮名の子音を重ねても打てる
+      'っ'
+        if ('ぁ'..='ゖ').contains(next)
+          && !SMALL_KANA.contains(next) =>
+      {
+        let mut styles: Vec<String> = vec![];
+        // 拗音は先頭の仮名の子音を先に並べる
+        let head_styles = match rest.get(1) {
+          Some('ゃ' | 'ゅ' | 'ょ') => self
+            .longest_match(&rest[..1])
+            .map_or(&[][..], |(_, styles)| styles),
+          _ => &[],
+        };
+        for style in head_styles.iter().chain(next_styles) {
+          let consonant = style.chars().next()?;
+          let consonant = consonant.to_string();
+          if !"aiueon".contains(&consonant)
+            && consonant.chars().all(|c| c.is_ascii_alphabetic())
+            && !styles.contains(&consonant)
+          {
+            styles.push(consonant);
+          }
+        }
+        if styles.is_empty() {
+          return None;
+        }
+        styles.extend(own_styles.iter().cloned());
+        Some(styles)
+      }
+      // 「ん」は次の仮名が母音、や行、な行で始まらなければ n 1 つでも打てる
+      'ん'
+        if own_styles.iter().any(|style| style == "nn")
+          && next_styles.iter().all(|style| {
+            !style.starts_with(['a', 'i', 'u', 'e', 'o', 'y', 'n'])
+          }) =>
+      {
+        let mut styles = vec!["n".to_owned()];
+        styles.extend(own_styles.iter().cloned());
+        Some(styles)
+      }
+      _ => None,
+    }
+  }
+}
+
+#[test]
+fn bundled() {
+  let table = RomanTable::bundled();
+  let kana: Vec<char> = "しゃしん".chars().collect();
+  assert_eq!(
+    table.longest_match(&kana),
+    Some((2, ["sha".to_owned(), "sya".to_owned()].as_slice()))
+  );
+  assert_eq!(
+    table.contextual(&"っちゃ".chars().collect::<Vec<_>>()),
+    Some(vec!["t".into(), "c".into(), "xtu".into(), "ltu".into()])
+  );
+  assert_eq!(
+    table.contextual(&"んか".chars().collect::<Vec<_>>()),
+    Some(vec!["n".into(), "nn".into(), "xn".into()])
+  );
+  for kana in ["んな", "んや", "んい", "ん"] {
+    assert_eq!(
+      table.contextual(&kana.chars().collect::<Vec<_>>()),
+      None
+    );
+  }
+}
+
+#[test]
+fn canonical_text() {
+  let table = RomanTable::parse(
+    "し	si shi
+か	ka
+",
+  )
+  .unwrap();
+  let swapped = RomanTable::parse(
+    "か	ka
+し	shi si
+",
+  )
+  .unwrap();
+  assert_eq!(table.canonical_text(), swapped.canonical_text());
+  assert_eq!(
+    table.canonical_text(),
+    "か	ka	false
+し	shi si	false
+"
+  );
+  let other = RomanTable::parse(
+    "し	si
+か	ka
+",
+  )
+  .unwrap();
+  assert_ne!(table.canonical_text(), other.canonical_text());
+}
+
+#[test]
+fn invalid_tables() {
+  use RomanTableError::*;
+  assert_eq!(
+    RomanTable::parse("# 表\nし\tsi shi\nし\tci\n").unwrap_err(),
+    DuplicatedKana {
+      line_num: 3,
+      first_line_num: 2,
+      kana: "し".into(),
+    }
+  );
+  assert_eq!(
+    RomanTable::parse("ん\tn nn\n").unwrap_err(),
+    AmbiguousStyles {
+      line_num: 1,
+      shorter: "n".into(),
+      longer: "nn".into(),
+    }
+  );
+  // 別の行の打ち方を打った時点で確定してしまう
+  assert_eq!(
+    RomanTable::parse("ちゃ\ttixya\nち\tti\n").unwrap_err(),
+    AmbiguousStyles {
+      line_num: 2,
+      shorter: "ti".into(),
+      longer: "tixya".into(),
+    }
+  );
+  assert!(RomanTable::bundled().overlay("ちゃ\ttixya\n").is_err());
+  assert!(matches!(
+    RomanTable::parse("つ\n").unwrap_err(),
+    InvalidRule { line_num: 1, .. }
+  ));
+  assert!(matches!(
+    RomanTable::parse("つ\tt\u{7}u\n").unwrap_err(),
+    InvalidRule { line_num: 1, .. }
+  ));
+}
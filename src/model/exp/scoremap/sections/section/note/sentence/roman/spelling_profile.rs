@@ -0,0 +1,122 @@
+use std::collections::HashMap;
+
+// 読み仮名ごとの好みの打ち方
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct SpellingProfile {
+  // 明示的に選んだ打ち方
+  preferred: HashMap<String, String>,
+  // これまでに使った打ち方の回数
+  used: HashMap<String, HashMap<String, u32>>,
+}
+
+impl SpellingProfile {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  // `仮名<TAB>打ち方[<TAB>回数]` の行を読む
+  // 回数のない行は好みの打ち方として、使った回数より優先する
+  pub fn parse(source: &str) -> Self {
+    let mut profile = Self::new();
+    for line in source.lines() {
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      let columns: Vec<_> = line.split_whitespace().collect();
+      match columns.as_slice() {
+        [kana, style] => profile.prefer(kana, style),
+        [kana, style, count] => {
+          if let Ok(count) = count.parse::<u32>() {
+            *profile.counts_mut(kana, style) += count;
+          }
+        }
+        _ => {}
+      }
+    }
+    profile
+  }
+
+  pub fn to_tsv(&self) -> String {
+    let mut lines: Vec<_> = self
+      .preferred
+      .iter()
+      .map(|(kana, style)| format!("{}\t{}", kana, style))
+      .collect();
+    lines.sort();
+    let mut used: Vec<_> = self
+      .used
+      .iter()
+      .flat_map(|(kana, counts)| {
+        counts.iter().map(move |(style, count)| {
+          format!("{}\t{}\t{}", kana, style, count)
+        })
+      })
+      .collect();
+    used.sort();
+    lines.append(&mut used);
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+  }
+
+  fn counts_mut(&mut self, kana: &str, style: &str) -> &mut u32 {
+    self
+      .used
+      .entry(kana.into())
+      .or_default()
+      .entry(style.into())
+      .or_default()
+  }
+
+  pub fn prefer(&mut self, kana: &str, style: &str) {
+    self.preferred.insert(kana.into(), style.into());
+  }
+
+  pub fn record(&mut self, kana: &str, style: &str) {
+    *self.counts_mut(kana, style) += 1;
+  }
+
+  // 大きいほど先に表示する
+  pub fn rank(&self, kana: &str, style: &str) -> (bool, u32) {
+    let preferred =
+      self.preferred.get(kana).is_some_and(|pref| pref == style);
+    let count = self
+      .used
+      .get(kana)
+      .and_then(|counts| counts.get(style))
+      .copied()
+      .unwrap_or(0);
+    (preferred, count)
+  }
+}
+
+#[test]
+fn prefer_used_styles() -> Result<(), super::RomanParseError> {
+  let mut profile =
+    SpellingProfile::parse("# 好み\nし\tsi\nし\tshi\t3\nち\tti\t1\n");
+  profile.record("ち", "chi");
+  profile.record("ち", "chi");
+
+  let mut shichi = super::RomanStr::new("しち")?;
+  assert_eq!(shichi.will_input_roman(), "siti");
+  profile.record("し", "shi");
+  shichi.prefer(&profile);
+  assert_eq!(shichi.will_input_roman(), "sichi");
+
+  assert_eq!(SpellingProfile::parse(&profile.to_tsv()), profile);
+  Ok(())
+}
+
+#[test]
+fn learn_from_spellings() -> Result<(), super::RomanParseError> {
+  let mut chichi = super::RomanStr::new("ちち")?;
+  for typed in "chit".chars() {
+    assert!(chichi.input(typed));
+  }
+  let mut profile = SpellingProfile::new();
+  for (kana, style) in chichi.spellings() {
+    profile.record(kana, style);
+  }
+  assert_eq!(profile.rank("ち", "chi"), (false, 1));
+  assert_eq!(profile.rank("ち", "ti"), (false, 0));
+  Ok(())
+}
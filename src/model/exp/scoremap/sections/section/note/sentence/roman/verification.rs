@@ -0,0 +1,161 @@
+// 読み仮名ごとに打てるはずのキーの並びを全て列挙し、ローマ字の判定と突き合わせる
+use super::{parse, RomanChar, RomanStr};
+use std::collections::BTreeSet;
+
+// 検証に使う仮名。表にある平仮名 1 文字全て
+fn kana() -> Vec<char> {
+  ('ぁ'..='ゖ')
+    .chain(Some('ー'))
+    .filter(|c| RomanStr::new(&c.to_string()).is_ok())
+    .collect()
+}
+
+// 促音を子音の重ね打ちで打つなら、次の打ち方はその子音で始まる
+fn is_consistent(chars: &[RomanChar], styles: &[&str]) -> bool {
+  chars
+    .iter()
+    .zip(styles.windows(2))
+    .all(|(roman_char, pair)| {
+      let doubled =
+        roman_char.kana() == "っ" && pair[0].chars().count() == 1;
+      !doubled || pair[1].starts_with(pair[0])
+    })
+}
+
+// 打ち方の組み合わせから作れる、打てるはずのキーの並び全て
+pub fn expected_sequences(yomigana: &str) -> BTreeSet<String> {
+  let chars: Vec<char> = yomigana.chars().collect();
+  let mut parsed = vec![];
+  parse(&mut parsed, &chars).expect("yomigana must be parsable");
+
+  let mut combinations: Vec<Vec<&str>> = vec![vec![]];
+  for roman_char in &parsed {
+    combinations = combinations
+      .iter()
+      .flat_map(|prefix| {
+        roman_char.styles().iter().map(move |style| {
+          let mut combination = prefix.clone();
+          combination.push(style.as_str());
+          combination
+        })
+      })
+      .collect();
+  }
+  combinations
+    .iter()
+    .filter(|styles| is_consistent(&parsed, styles))
+    .map(|styles| styles.concat())
+    .collect()
+}
+
+// 途中で弾かれずに打ち終えられるか
+pub fn verify_sequence(yomigana: &str, sequence: &str) {
+  let mut roman = RomanStr::new(yomigana).unwrap();
+  for (index, typed) in sequence.chars().enumerate() {
+    assert!(
+      !roman.completed(),
+      "{}: {} は打ち過ぎ",
+      yomigana,
+      sequence
+    );
+    assert!(
+      roman.input(typed),
+      "{}: {} の {} 文字目が弾かれた",
+      yomigana,
+      sequence,
+      index + 1
+    );
+  }
+  assert!(
+    roman.completed(),
+    "{}: {} で打ち終わらない",
+    yomigana,
+    sequence
+  );
+}
+
+// 判定が受け付けるキーの並びを、打ち方に出てくるキーを総当たりして求める
+pub fn accepted_sequences(yomigana: &str) -> BTreeSet<String> {
+  let expected = expected_sequences(yomigana);
+  let keys: BTreeSet<char> = expected
+    .iter()
+    .flat_map(|sequence| sequence.chars())
+    .collect();
+  let max_len = expected
+    .iter()
+    .map(|sequence| sequence.len())
+    .max()
+    .unwrap_or(0);
+
+  let mut accepted = BTreeSet::new();
+  let mut stack =
+    vec![(RomanStr::new(yomigana).unwrap(), String::new())];
+  while let Some((roman, typed)) = stack.pop() {
+    if roman.completed() {
+      accepted.insert(typed);
+      continue;
+    }
+    if max_len <= typed.len() {
+      continue;
+    }
+    for &key in &keys {
+      let mut next = roman.clone();
+      if next.input(key) {
+        stack.push((next, format!("{}{}", typed, key)));
+      }
+    }
+  }
+  accepted
+}
+
+pub fn verify(yomigana: &str) {
+  for sequence in expected_sequences(yomigana) {
+    verify_sequence(yomigana, &sequence);
+  }
+}
+
+#[test]
+fn hand_written() {
+  assert_eq!(
+    expected_sequences("っち").into_iter().collect::<Vec<_>>(),
+    ["cchi", "ltuchi", "ltuti", "tti", "xtuchi", "xtuti"]
+  );
+  for yomigana in ["こんにちは", "おもちゃ", "ちっちゃい", "かんじ"]
+  {
+    verify(yomigana);
+  }
+}
+
+#[test]
+fn bigrams() {
+  let kana = kana();
+  for first in &kana {
+    for second in &kana {
+      let yomigana: String =
+        [first, second].iter().copied().collect();
+      verify(&yomigana);
+      assert_eq!(
+        accepted_sequences(&yomigana),
+        expected_sequences(&yomigana),
+        "{}",
+        yomigana
+      );
+    }
+  }
+}
+
+// 全ての組を試すと時間がかかるので、cargo test -- --ignored で走らせる
+#[test]
+#[ignore]
+fn trigrams() {
+  let kana = kana();
+  for first in &kana {
+    for second in &kana {
+      for third in &kana {
+        let yomigana: String =
+          [first, second, third].iter().copied().collect();
+        verify(&yomigana);
+      }
+    }
+  }
+}
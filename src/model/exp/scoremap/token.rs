@@ -9,6 +9,8 @@ pub enum TokenContent {
   Command(String),
   Lyrics(String),
   Yomigana(RomanStr),
+  // 英語の歌詞などをそのまま打つ文字列
+  Latin(String),
   Caption(String),
   Section(String),
   Time(MinuteSecond),
@@ -0,0 +1,352 @@
+use super::{
+  lexer::{pattern::*, tempo::Tempo},
+  metadata::ScoremapMetadata,
+  sections::section::note::{sentence::latin::LatinStr, NoteContent},
+  Scoremap,
+};
+use crate::model::exp::time::{MinuteSecond, Seconds};
+use regex::Regex;
+
+#[cfg(test)]
+mod tests;
+
+fn time_lines(time: Seconds, curr_minutes: &mut i64) -> Vec<String> {
+  let millis = time.as_millis();
+  let minutes = millis / 60_000;
+  let mut lines = vec![];
+  if minutes != *curr_minutes {
+    lines.push(format!("|{}", minutes));
+    *curr_minutes = minutes;
+  }
+  lines.push(format!(
+    "*{}.{:03}",
+    millis % 60_000 / 1000,
+    millis % 1000
+  ));
+  lines
+}
+
+// 歌詞をそのまま打つなら、打つ文字列は省略する
+fn latin_line(lyrics: &str, text: &str) -> String {
+  if LatinStr::new(lyrics).text() == LatinStr::new(text).text() {
+    "=".into()
+  } else {
+    format!("={}", LatinStr::new(text).text())
+  }
+}
+
+fn render(scoremap: &Scoremap) -> Vec<String> {
+  let mut lines = vec![];
+
+  for (key, value) in scoremap.metadata.properties() {
+    lines.push(format!(":{} {}", key, value));
+  }
+  lines.push("".into());
+
+  lines.push("[start]".into());
+  let sections: Vec<_> = scoremap
+    .sections
+    .iter()
+    .map(|section| section.name())
+    .collect();
+  let mut notes: Vec<_> = scoremap
+    .sections
+    .iter()
+    .enumerate()
+    .flat_map(|(section_index, section)| {
+      section.iter().enumerate().map(move |(note_index, note)| {
+        (section_index, note_index, note)
+      })
+    })
+    .collect();
+  // 最後の空白ノーツは読み込み時に追加されたもの
+  if let Some((_, _, last)) = notes.last() {
+    if let NoteContent::Blank = last.content() {
+      notes.pop();
+    }
+  }
+
+  let mut curr_minutes = 0;
+  for (section_index, note_index, note) in notes.iter() {
+    lines.append(&mut time_lines(
+      note.duration().start(),
+      &mut curr_minutes,
+    ));
+    if *note_index == 0 && !sections[*section_index].is_empty() {
+      lines.push(format!("@{}", sections[*section_index]));
+    }
+    match note.content() {
+      NoteContent::Sentence { sentence, .. } => {
+        lines.push(sentence.origin().into());
+        if sentence.is_latin() {
+          lines
+            .push(latin_line(sentence.origin(), sentence.hiragana()));
+        } else {
+          lines.push(format!(":{}", sentence.hiragana()));
+        }
+      }
+      NoteContent::Caption(caption) => {
+        lines.push(format!(">> {}", caption));
+      }
+      NoteContent::Blank => {}
+    }
+    lines.push("".into());
+  }
+  if let Some((_, _, last)) = notes.last() {
+    lines.append(&mut time_lines(
+      last.duration().end(),
+      &mut curr_minutes,
+    ));
+  }
+  lines.push("[end]".into());
+  lines
+}
+
+pub fn write(scoremap: &Scoremap) -> String {
+  render(scoremap).join("\n") + "\n"
+}
+
+struct LineNormalizer {
+  comment: Regex,
+  seconds: Regex,
+  beat: Regex,
+  minutes: Regex,
+  tempo_command: Regex,
+  command: Regex,
+  caption: Regex,
+  property: Regex,
+  yomigana: Regex,
+  latin: Regex,
+  section: Regex,
+  curr_mise: MinuteSecond,
+  lyrics: String,
+  tempo: Option<Tempo>,
+}
+
+impl LineNormalizer {
+  fn new() -> Self {
+    let compile = |pattern| Regex::new(pattern).unwrap();
+    Self {
+      comment: compile(COMMENT),
+      seconds: compile(SECONDS),
+      beat: compile(BEAT),
+      minutes: compile(MINUTES),
+      tempo_command: compile(TEMPO),
+      command: compile(COMMAND),
+      caption: compile(CAPTION),
+      property: compile(PROPERTY),
+      yomigana: compile(YOMIGANA),
+      latin: compile(LATIN),
+      section: compile(SECTION),
+      curr_mise: MinuteSecond::new(),
+      lyrics: String::new(),
+      tempo: None,
+    }
+  }
+
+  fn minutes(&self) -> i64 {
+    self.curr_mise.as_seconds().as_millis() / 60_000
+  }
+
+  // 時間指定は、その分の中での秒に揃える
+  fn advance(&mut self, specified: MinuteSecond) -> Option<String> {
+    if specified <= self.curr_mise {
+      return None;
+    }
+    self.curr_mise = specified;
+    let mut minutes = self.minutes();
+    time_lines(specified.into(), &mut minutes).pop()
+  }
+
+  // 字句解析器と同じ順で行を読み、意味が同じ行を同じ文字列にする
+  // 譜面に影響しない行は None
+  fn normalize(&mut self, line: &str) -> Option<String> {
+    if self.comment.is_match(line) {
+      return None;
+    }
+    if let Some(captures) = self.seconds.captures(line) {
+      let seconds: f64 = captures[1].parse().ok()?;
+      return self.advance(self.curr_mise.seconds(seconds));
+    }
+    if let Some(captures) = self.beat.captures(line) {
+      let number = |index| {
+        captures
+          .get(index)
+          .map_or(Some(0), |m| m.as_str().parse().ok())
+      };
+      if let (Some(tempo), Some(bar), Some(beat), Some(tick)) =
+        (self.tempo, number(1), number(2), number(3))
+      {
+        if 0 < bar && 0 < beat {
+          return self
+            .advance(tempo.position(bar, beat, tick).into());
+        }
+      }
+      return Some(line.into());
+    }
+    if let Some(captures) = self.minutes.captures(line) {
+      let minutes: u32 = captures[1].parse().ok()?;
+      self.curr_mise = self.curr_mise.minutes(minutes).seconds(0.0);
+      return Some(format!("|{}", minutes));
+    }
+    if let Some(captures) = self.tempo_command.captures(line) {
+      if let Ok(bpm) = captures[1].parse() {
+        let at = self.curr_mise.as_seconds();
+        self.tempo = Some(self.tempo.map_or_else(
+          || Tempo::new(bpm),
+          |prev| prev.change(at, bpm),
+        ));
+      }
+      return None;
+    }
+    if let Some(captures) = self.command.captures(line) {
+      if &captures[1] == "break" {
+        return None;
+      }
+      return Some(format!("[{}]", &captures[1]));
+    }
+    if let Some(captures) = self.caption.captures(line) {
+      return Some(format!(">> {}", &captures[1]));
+    }
+    if let Some(captures) = self.property.captures(line) {
+      // 値は読み込んだ後の書き方に揃える
+      let mut metadata = ScoremapMetadata::new();
+      if !METADATA_KEYS.contains(&&captures[1])
+        && !ScoremapMetadata::is_extension(&captures[1])
+      {
+        return None;
+      }
+      if metadata.insert(&captures[1], &captures[2]).is_err() {
+        return Some(line.into());
+      }
+      if let Some(bpm) = metadata.bpm {
+        self.tempo = Some(Tempo::new(bpm));
+      }
+      let (key, value) = metadata.properties().pop()?;
+      return Some(format!(":{} {}", key, value));
+    }
+    if let Some(captures) = self.yomigana.captures(line) {
+      self.lyrics.clear();
+      return Some(format!(":{}", &captures[1]));
+    }
+    if let Some(captures) = self.latin.captures(line) {
+      let lyrics = std::mem::take(&mut self.lyrics);
+      let text = captures[1].trim();
+      if text.is_empty() {
+        return Some("=".into());
+      }
+      return Some(latin_line(&lyrics, text));
+    }
+    if let Some(captures) = self.section.captures(line) {
+      return Some(format!("@{}", captures[1].trim()));
+    }
+    self.lyrics.push_str(line);
+    Some(line.into())
+  }
+}
+
+fn push_trivia(
+  lines: &mut Vec<String>,
+  source: &[(&str, Option<String>)],
+) {
+  for (line, normalized) in source {
+    if normalized.is_none() {
+      lines.push(line.to_string());
+    }
+  }
+}
+
+// 元の譜面のコメントや書式を残したまま書き出す
+pub fn write_lossless(scoremap: &Scoremap, original: &str) -> String {
+  let eol = if original.contains("\r\n") {
+    "\r\n"
+  } else {
+    "\n"
+  };
+  let mut source_minutes = vec![];
+  let source: Vec<(&str, Option<String>)> = {
+    let mut normalizer = LineNormalizer::new();
+    original
+      .lines()
+      .map(|line| {
+        let normalized = normalizer.normalize(line);
+        source_minutes.push(normalizer.minutes());
+        (line, normalized)
+      })
+      .collect()
+  };
+  let mut rendered: Vec<(String, String)> = {
+    let mut normalizer = LineNormalizer::new();
+    render(scoremap)
+      .into_iter()
+      .filter_map(|line| {
+        let normalized = normalizer.normalize(&line)?;
+        Some((line, normalized))
+      })
+      .collect()
+  };
+  // プロパティは元の譜面の並び順に合わせる
+  let properties = rendered
+    .iter()
+    .take_while(|(line, _)| line.starts_with(':'))
+    .count();
+  rendered[..properties].sort_by_key(|(_, normalized)| {
+    source
+      .iter()
+      .position(|(_, source)| source.as_ref() == Some(normalized))
+      .unwrap_or(source.len())
+  });
+
+  let mut lines: Vec<String> = vec![];
+  let mut cursor = 0;
+  for (index, (line, normalized)) in rendered.iter().enumerate() {
+    let found = source[cursor..]
+      .iter()
+      .position(|(_, source)| source.as_ref() == Some(normalized));
+    if let Some(offset) = found {
+      push_trivia(&mut lines, &source[cursor..cursor + offset]);
+      lines.push(source[cursor + offset].0.into());
+      cursor += offset + 1;
+      continue;
+    }
+    // 拍で書かれた時間指定は `|` なしで分をまたげる
+    if normalized.starts_with('|') {
+      let reached = rendered
+        .get(index + 1)
+        .and_then(|(_, next)| {
+          source[cursor..]
+            .iter()
+            .position(|(_, source)| source.as_ref() == Some(next))
+        })
+        .is_some_and(|offset| {
+          format!("|{}", source_minutes[cursor + offset])
+            == *normalized
+        });
+      if reached {
+        continue;
+      }
+    }
+    // 後で使われない元の行は、この行で置き換えられたものとみなす
+    let replaced = source[cursor..]
+      .iter()
+      .position(|(_, source)| source.is_some())
+      .map(|offset| cursor + offset)
+      .filter(|&replaced| {
+        rendered[index + 1..]
+          .iter()
+          .all(|(_, rest)| source[replaced].1.as_ref() != Some(rest))
+      });
+    if let Some(replaced) = replaced {
+      push_trivia(&mut lines, &source[cursor..replaced]);
+      cursor = replaced + 1;
+    }
+    lines.push(line.clone());
+  }
+  push_trivia(&mut lines, &source[cursor..]);
+
+  let mut written = lines.join(eol);
+  if original.is_empty() || original.ends_with('\n') {
+    written.push_str(eol);
+  }
+  written
+}
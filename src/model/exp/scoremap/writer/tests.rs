@@ -0,0 +1,196 @@
+use crate::model::exp::scoremap::{
+  sections::section::note::NoteContent, Scoremap, ScoremapError,
+};
+
+const SAMPLE: &str =
+  include_str!("../../../../../score/sampleScore.tsc");
+
+fn assert_same_notes(expected: &Scoremap, actual: &Scoremap) {
+  assert_eq!(expected.metadata, actual.metadata);
+  for (section_idx, (expected, actual)) in expected
+    .sections
+    .iter()
+    .zip(actual.sections.iter())
+    .enumerate()
+  {
+    for (note_idx, (expected, actual)) in
+      expected.iter().zip(actual.iter()).enumerate()
+    {
+      assert_eq!(
+        expected.content(),
+        actual.content(),
+        "section_idx: {} | note_idx: {}",
+        section_idx,
+        note_idx
+      );
+      assert_eq!(
+        expected.duration(),
+        actual.duration(),
+        "section_idx: {} | note_idx: {}",
+        section_idx,
+        note_idx
+      );
+    }
+    assert_eq!(
+      expected.len(),
+      actual.len(),
+      "section_idx: {}",
+      section_idx
+    );
+  }
+  assert_eq!(expected.sections.len(), actual.sections.len());
+}
+
+#[test]
+fn sample_lossless() -> Result<(), ScoremapError> {
+  let score = Scoremap::from_str(SAMPLE, |config| {
+    config.ignore_unsupported_property(true)
+  })?;
+  assert_eq!(SAMPLE, score.to_tsc_lossless(SAMPLE));
+  Ok(())
+}
+
+#[test]
+fn sample_normalized() -> Result<(), ScoremapError> {
+  let score = Scoremap::from_str(SAMPLE, |config| {
+    config.ignore_unsupported_property(true)
+  })?;
+  let written = score.to_tsc();
+  let reloaded = Scoremap::from_str(&written, |config| config)?;
+  assert_same_notes(&score, &reloaded);
+  assert_eq!(written, reloaded.to_tsc());
+  Ok(())
+}
+
+#[test]
+fn normalized() -> Result<(), ScoremapError> {
+  let score = Scoremap::from_str(
+    r#"
+# Sample 1
+:song_data void.ogg
+:title   TEST
+:bpm 222.22
+
+[start]
+*2.22
+打鍵テスト
+:だけんてすと
+
+*3.0
+>>間奏
+*4.0
+|1
+*5.5
+@サビ
+えっ
+:えっ
+*7.0
+[end]
+"#,
+    |config| config,
+  )?;
+  assert_eq!(
+    score.to_tsc(),
+    r#":title TEST
+:song_data void.ogg
+:bpm 222.22
+
+[start]
+*0.000
+
+*2.220
+打鍵テスト
+:だけんてすと
+
+*3.000
+>> 間奏
+
+*4.000
+
+|1
+*5.500
+@サビ
+えっ
+:えっ
+
+*7.000
+[end]
+"#
+  );
+  Ok(())
+}
+
+#[test]
+fn keeps_comments_around_changes() -> Result<(), ScoremapError> {
+  let original = "# 譜面\r\n:title TEST\r\n:song_data void.ogg\r\n\r\n[start]\r\n*1.0\r\n# 一行目\r\n打鍵テスト\r\n:だけんてすと\r\n\r\n*2.0\r\n# 二行目\r\nえっ\r\n:えっ\r\n*3.0\r\n[end]\r\n";
+  let edited = Scoremap::from_str(
+    &original.replace("えっ\r\n:えっ", "おっ\r\n:おっ"),
+    |config| config,
+  )?;
+  assert_eq!(
+    edited.to_tsc_lossless(original),
+    "# 譜面\r\n:title TEST\r\n:song_data void.ogg\r\n\r\n[start]\r\n*1.0\r\n# 一行目\r\n打鍵テスト\r\n:だけんてすと\r\n\r\n*2.0\r\n# 二行目\r\nおっ\r\n:おっ\r\n*3.0\r\n[end]\r\n"
+  );
+  Ok(())
+}
+
+#[test]
+fn beats_lossless() -> Result<(), ScoremapError> {
+  let original = r#":bpm 120
+
+[start]
+*1:2
+>> 前奏
+[bpm 60]
+*10:1
+初めの歌詞
+:はじめのかし
+
+*19:1
+次の歌詞
+:つぎのかし
+
+*20:1
+[end]
+"#;
+  let score = Scoremap::from_str(original, |config| config)?;
+  assert_eq!(original, score.to_tsc_lossless(original));
+  Ok(())
+}
+
+#[test]
+fn latin_lossless() -> Result<(), ScoremapError> {
+  let original = r#":song_data void.ogg
+
+[start]
+*1.0
+Fly me to the moon
+=
+
+*2.0
+Let me play among the stars
+=Let me play  among the stars!
+
+*3.0
+[end]
+"#;
+  let score = Scoremap::from_str(original, |config| config)?;
+  let texts: Vec<_> = score
+    .sections
+    .iter()
+    .flat_map(|section| section.iter())
+    .filter_map(|note| match note.content() {
+      NoteContent::Sentence { sentence, .. } => {
+        Some(sentence.hiragana().to_owned())
+      }
+      _ => None,
+    })
+    .collect();
+  assert_eq!(
+    texts,
+    vec!["Fly me to the moon", "Let me play among the stars!"]
+  );
+  assert_eq!(original, score.to_tsc_lossless(original));
+  assert!(score.to_tsc().contains("\n=\n"));
+  Ok(())
+}
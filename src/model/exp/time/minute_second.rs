@@ -1,9 +1,25 @@
 use super::seconds::Seconds;
-use std::{cmp::Ordering, ops::SubAssign};
+use std::{
+  cmp::Ordering,
+  fmt::{Display, Formatter},
+  ops::SubAssign,
+};
 
 #[derive(Debug)]
 pub enum DurationError {
-  InvalidArguments(String),
+  InvalidArguments { from: f64, to: f64 },
+}
+
+impl Display for DurationError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+    match self {
+      DurationError::InvalidArguments { from, to } => write!(
+        f,
+        "{:.3} 秒から {:.3} 秒までの間になっていません。",
+        from, to
+      ),
+    }
+  }
 }
 
 pub type DurationResult<T> = Result<T, DurationError>;
@@ -17,9 +33,7 @@ pub struct Duration {
 impl Duration {
   pub fn new(from: f64, to: f64) -> DurationResult<Self> {
     if from >= to {
-      return Err(DurationError::InvalidArguments(
-        format!("`from`  must be before than `to`; Actual: {{ from: {}, to: {} }}", from, to),
-      ));
+      return Err(DurationError::InvalidArguments { from, to });
     }
     Ok(Self {
       from: from.into(),
@@ -50,6 +64,22 @@ impl Duration {
     }
   }
 
+  // 始まりと終わりの時間を factor 倍にする
+  pub fn scaled(&self, factor: f64) -> Self {
+    Self {
+      from: (self.from.as_f64() * factor).into(),
+      to: (self.to.as_f64() * factor).into(),
+    }
+  }
+
+  pub fn start(&self) -> Seconds {
+    self.from
+  }
+
+  pub fn end(&self) -> Seconds {
+    self.to
+  }
+
   pub fn includes(&self, time: &Seconds) -> bool {
     self.from <= *time && *time < self.to
   }
@@ -62,6 +92,7 @@ impl Duration {
 }
 
 #[test]
+#[allow(clippy::useless_conversion)]
 fn duration() -> DurationResult<()> {
   assert!(Duration::new(1.0.into(), 0.0.into()).is_err());
   assert!(Duration::new(0.0.into(), 0.0.into()).is_err());
@@ -128,6 +159,16 @@ impl From<MinuteSecond> for Seconds {
   }
 }
 
+impl From<Seconds> for MinuteSecond {
+  fn from(seconds: Seconds) -> Self {
+    let millis = seconds.as_millis().max(0);
+    MinuteSecond {
+      minutes: (millis / 60_000) as u32,
+      seconds: Seconds::new((millis % 60_000) as f64 / 1000.0),
+    }
+  }
+}
+
 impl PartialOrd for MinuteSecond {
   fn partial_cmp(&self, other: &MinuteSecond) -> Option<Ordering> {
     if self.minutes == other.minutes {
@@ -16,6 +16,10 @@ impl Seconds {
     self.0 as f64 / 1000.0
   }
 
+  pub fn as_millis(&self) -> i64 {
+    self.0
+  }
+
   pub fn max(self, other: Self) -> Self {
     Self(self.0.max(other.0))
   }
@@ -67,18 +71,21 @@ impl Div for Seconds {
   }
 }
 
+#[allow(clippy::unnecessary_cast)]
 impl PartialEq<Seconds> for f64 {
   fn eq(&self, other: &Seconds) -> bool {
     ((self * 1e3) as i64 - other.0 as i64).abs() <= 10
   }
 }
 
+#[allow(clippy::unnecessary_cast)]
 impl PartialEq<f64> for Seconds {
   fn eq(&self, other: &f64) -> bool {
     (self.0 as i64 - (other * 1e3) as i64) <= 10
   }
 }
 
+#[allow(clippy::unnecessary_cast)]
 impl PartialEq for Seconds {
   fn eq(&self, other: &Self) -> bool {
     (self.0 as i64 - other.0 as i64) <= 10
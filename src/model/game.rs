@@ -1,20 +1,34 @@
 use super::exp::{
-  game_activity::GameActivity,
+  game_activity::{GameActivity, LifeChange, LifeRules},
   note::TypeResult,
-  scoremap::{
-    lexer::ScoremapLexError, MusicInfo, Scoremap, ScoremapError,
-    ScoremapMetadata,
+  scoremap::{MusicInfo, Scoremap, ScoremapError, ScoremapMetadata},
+  sentence::{
+    latin::LatinOptions,
+    roman::{InputScheme, RomanParseError, SpellingProfile},
+    Sentence,
   },
-  sentence::{roman::RomanParseError, Sentence},
   time::Seconds,
 };
 use std::io::Error;
 use MusicalTyperError::*;
 use MusicalTyperEvent::*;
 
+mod clock;
+mod ghost;
+mod headless;
+mod key_profile;
+mod replay;
+mod scoring;
 #[cfg(test)]
 mod tests;
 
+pub use clock::*;
+pub use ghost::*;
+pub use headless::*;
+pub use key_profile::*;
+pub use replay::*;
+pub use scoring::*;
+
 #[derive(Debug, Clone, PartialEq)]
 pub enum MusicalTypeResult {
   Correct,
@@ -35,11 +49,20 @@ impl From<TypeResult> for MusicalTypeResult {
 #[derive(Debug, Clone, PartialEq)]
 pub enum MusicalTyperEvent {
   PlayBgm(String),
+  EnterSection(String),
   UpdateSentence(Sentence),
   MissedSentence(Sentence),
   CompletedSentence(Sentence),
   DidPerfectSection,
   Typed(MusicalTypeResult),
+  // 倍率の上がるコンボ数に達した
+  ComboMilestone(u32),
+  // 続いていたコンボが途切れた。途切れる前のコンボ数
+  ComboBroken(u32),
+  // ライフゲージが尽きて、譜面の途中で終わった
+  Failed,
+  // 曲の始まりからこの時間に移った。曲の再生位置を合わせる
+  Seeked(Seconds),
   EndOfScore,
 }
 
@@ -64,46 +87,117 @@ impl From<ScoremapError> for MusicalTyperError {
   }
 }
 impl From<RomanParseError> for MusicalTyperError {
-  fn from(_err: RomanParseError) -> Self {
-    MusicalTyperError::ScoremapBuildError(ScoremapError::LexError(
-      ScoremapLexError::InvalidStatementDefinition {
-        line_num: 1,
-        reason: "ふりがなでのそのような平仮名の並びは未対応です。",
-      },
-    ))
+  fn from(err: RomanParseError) -> Self {
+    MusicalTyperError::ScoremapBuildError(err.into())
   }
 }
 
 pub type Point = u32;
 
+#[derive(Clone)]
 pub struct MusicalTyperConfig {
-  wrong_type: Point,
-  correct_type: Point,
-  missed_sentence: Point,
-  complete_sentence: Point,
-  perfect_sentence: Point,
-  perfect_section: Point,
+  scoring: ScoringProfile,
+  input_scheme: InputScheme,
+  latin_options: LatinOptions,
+  spelling_profile: SpellingProfile,
+  key_profile: KeyProfile,
+  trailing_n: bool,
+  life_gauge: Option<LifeRules>,
+  practice: bool,
+  speed: f64,
 }
 
+// 曲を流す速さの範囲
+pub const SPEED_RANGE: (f64, f64) = (0.5, 1.5);
+
 impl Default for MusicalTyperConfig {
   fn default() -> Self {
     MusicalTyperConfig {
-      wrong_type: 30,
-      correct_type: 10,
-      missed_sentence: 2,
-      complete_sentence: 50,
-      perfect_sentence: 100,
-      perfect_section: 300,
+      scoring: ScoringProfile::default(),
+      input_scheme: InputScheme::Standard,
+      latin_options: LatinOptions::default(),
+      spelling_profile: SpellingProfile::new(),
+      key_profile: KeyProfile::new(),
+      trailing_n: false,
+      life_gauge: None,
+      practice: false,
+      speed: 1.0,
     }
   }
 }
 
+impl MusicalTyperConfig {
+  pub fn scoring(mut self, profile: ScoringProfile) -> Self {
+    self.scoring = profile;
+    self
+  }
+
+  pub fn scoring_profile(&self) -> &ScoringProfile {
+    &self.scoring
+  }
+
+  pub fn input_scheme(mut self, scheme: InputScheme) -> Self {
+    self.input_scheme = scheme;
+    self
+  }
+
+  // 英語の歌詞の判定の仕方
+  pub fn latin_options(mut self, options: LatinOptions) -> Self {
+    self.latin_options = options;
+    self
+  }
+
+  // 文の末尾の「ん」を n 1 つで打ち終えてよいか
+  pub fn trailing_n(mut self, accept: bool) -> Self {
+    self.trailing_n = accept;
+    self
+  }
+
+  // ライフゲージを使い、尽きたら途中で終える
+  pub fn life_gauge(mut self, rules: LifeRules) -> Self {
+    self.life_gauge = Some(rules);
+    self
+  }
+
+  // 練習では一時停止や移動、区間の繰り返しができる代わりにリプレイを残さない
+  pub fn practice(mut self, practice: bool) -> Self {
+    self.practice = practice;
+    self
+  }
+
+  // 曲を speed 倍の速さで流し、譜面の時間もそれに合わせる
+  pub fn speed(mut self, speed: f64) -> Self {
+    self.speed = speed.clamp(SPEED_RANGE.0, SPEED_RANGE.1);
+    self
+  }
+
+  pub fn spelling_profile(
+    mut self,
+    profile: SpellingProfile,
+  ) -> Self {
+    self.spelling_profile = profile;
+    self
+  }
+
+  // これまでの遊びで集めた、キーごとの打ち間違えと速さ
+  pub fn key_profile(mut self, profile: KeyProfile) -> Self {
+    self.key_profile = profile;
+    self
+  }
+}
+
 pub struct MusicalTyper {
   activity: GameActivity,
   metadata: ScoremapMetadata,
   accumulated_time: Seconds,
+  offset: Seconds,
   event_queue: Vec<MusicalTyperEvent>,
   config: MusicalTyperConfig,
+  // 始めたときの設定と、与えた時間と打鍵全て
+  replay: Replay,
+  paused: bool,
+  // 譜面上のこの区間を繰り返す
+  loop_range: Option<(Seconds, Seconds)>,
 }
 
 impl MusicalTyper {
@@ -112,11 +206,33 @@ impl MusicalTyper {
     config: MusicalTyperConfig,
   ) -> Result<Self, MusicalTyperError> {
     let mut event_queue = vec![];
-    let activity = GameActivity::new(score.sections);
+    let replay = Replay::new(0, &config, vec![]);
+    let score = if config.speed == 1.0 {
+      score
+    } else {
+      score.at_speed(config.speed)
+    };
+    let mut sections = score.sections;
+    for (line_num, sentence) in sections.sentences_mut() {
+      sentence.set_input_scheme(&config.input_scheme).map_err(
+        |err| ScoremapError::InvalidYomigana { line_num, err },
+      )?;
+      if config.trailing_n {
+        sentence.accept_trailing_n();
+      }
+      sentence.prefer_spellings(&config.spelling_profile);
+      sentence.set_latin_options(config.latin_options);
+    }
+    let mut activity = GameActivity::new(sections);
+    if let Some(rules) = &config.life_gauge {
+      activity.enable_life_gauge(rules.clone());
+    }
+    activity.set_speed(config.speed);
 
     let metadata = score.metadata;
-    if let Some(song_data) = metadata.get("song_data") {
-      event_queue.push(PlayBgm(song_data.into()));
+    let offset = metadata.offset;
+    if let Some(song_data) = &metadata.song_data {
+      event_queue.push(PlayBgm(song_data.to_string_lossy().into()));
     } else {
       return Err(SongDataNotFound);
     }
@@ -125,8 +241,12 @@ impl MusicalTyper {
       activity,
       metadata,
       accumulated_time: 0.0.into(),
+      offset,
       event_queue,
       config,
+      replay,
+      paused: false,
+      loop_range: None,
     })
   }
 
@@ -135,22 +255,44 @@ impl MusicalTyper {
   }
 
   #[must_use]
+  #[allow(clippy::unnecessary_map_or)]
   pub fn key_press(
     &mut self,
     typed: impl Iterator<Item = char>,
   ) -> Vec<MusicalTyperEvent> {
     let prev_sentence = self.activity.current_sentence();
     let prev_completed = prev_sentence.completed();
+    let prev_failed = self.activity.is_failed();
+    if self.paused {
+      return vec![];
+    }
     for typed in typed {
+      if !self.config.practice {
+        self
+          .replay
+          .entries
+          .push(ReplayEntry::Key(self.accumulated_time, typed));
+      }
       use super::exp::section::note::TypeResult::*;
+      let prev_combo = self.activity.score().combo();
       let result = self.activity.input(typed);
-      let point = match result {
-        Succeed => self.config.correct_type as i32,
-        Mistaken => -(self.config.wrong_type as i32),
-        _ => 0,
+      let combo = self.activity.score().combo();
+      let scoring = &self.config.scoring;
+      let (point, combo_event) = match result {
+        Succeed => (
+          scoring.correct_type_point(combo),
+          Some(ComboMilestone(combo))
+            .filter(|_| scoring.is_combo_milestone(combo)),
+        ),
+        Mistaken => (
+          -(scoring.wrong_type as i32),
+          Some(ComboBroken(prev_combo)).filter(|_| 0 < prev_combo),
+        ),
+        _ => (0, None),
       };
       self.activity.point(point);
       self.event_queue.push(Typed(result.into()));
+      self.event_queue.extend(combo_event);
     }
     let curr_sentence = self.activity.current_sentence();
     let curr_completed = curr_sentence.completed();
@@ -162,7 +304,9 @@ impl MusicalTyper {
         .current_section()
         .map_or(false, |section| 1.0 <= section.accuracy())
       {
-        self.activity.point(self.config.perfect_section as i32);
+        self
+          .activity
+          .point(self.config.scoring.perfect_section as i32);
         events.push(DidPerfectSection);
       }
       if self
@@ -170,11 +314,21 @@ impl MusicalTyper {
         .current_note()
         .map_or(false, |note| 1.0 <= note.accuracy())
       {
-        self.activity.point(self.config.perfect_sentence as i32);
+        self
+          .activity
+          .point(self.config.scoring.perfect_sentence as i32);
+        self.activity.change_life(LifeChange::PerfectSentence);
       }
-      self.activity.point(self.config.complete_sentence as i32);
+      self
+        .activity
+        .point(self.config.scoring.complete_sentence as i32);
+      self.activity.change_life(LifeChange::CompletedSentence);
+      self.record_spellings(&curr_sentence);
       events.push(CompletedSentence(prev_sentence));
     }
+    if !prev_failed && self.activity.is_failed() {
+      events.push(Failed);
+    }
 
     [self.pack_events(), events].concat()
   }
@@ -184,13 +338,28 @@ impl MusicalTyper {
     &mut self,
     delta_time: Seconds,
   ) -> Vec<MusicalTyperEvent> {
+    if self.paused {
+      return vec![];
+    }
     self.accumulated_time += delta_time;
+    if let Some((from, to)) = self.loop_range {
+      if to <= self.score_time() {
+        return self.seek(from);
+      }
+    }
+    if !self.config.practice {
+      self
+        .replay
+        .entries
+        .push(ReplayEntry::Tick(self.accumulated_time));
+    }
 
     let completed = self.activity.current_sentence().completed();
     let prev_sentence = self.activity.current_sentence();
     let prev_note_id = self.activity.current_note_id();
+    let prev_section_index = self.activity.current_section_index();
 
-    self.activity.update_time(self.accumulated_time);
+    self.activity.update_time(self.score_time());
 
     if self.activity.is_game_over() {
       return vec![EndOfScore];
@@ -200,13 +369,176 @@ impl MusicalTyper {
 
     let mut events = vec![];
     if !completed && (prev_note_id != curr_note_id) {
-      self.activity.point(-(self.config.missed_sentence as i32));
+      self
+        .activity
+        .point(-(self.config.scoring.missed_sentence as i32));
+      self.activity.change_life(LifeChange::MissedSentence);
+      self.record_spellings(&prev_sentence);
       events.push(MissedSentence(prev_sentence));
+      if self.activity.is_failed() {
+        events.push(Failed);
+      }
+    }
+    if prev_section_index != self.activity.current_section_index() {
+      events.push(EnterSection(self.section_name().into()));
+    }
+
+    [self.pack_events(), events].concat()
+  }
+
+  // リプレイの記録を順に与える。同じ時点の打鍵はまとめて打つ
+  #[must_use]
+  pub fn feed(
+    &mut self,
+    entries: &[ReplayEntry],
+  ) -> Vec<MusicalTyperEvent> {
+    let mut events = vec![];
+    for batch in entries.chunk_by(ReplayEntry::same_batch) {
+      match batch {
+        [ReplayEntry::Tick(time)] => {
+          let delta = *time - self.accumulated_time;
+          events.append(&mut self.elapse_time(delta));
+        }
+        keys => {
+          let keys = keys.iter().filter_map(|entry| match entry {
+            ReplayEntry::Key(_, key) => Some(*key),
+            _ => None,
+          });
+          events.append(&mut self.key_press(keys));
+        }
+      }
     }
+    events
+  }
+
+  pub fn pause(&mut self) {
+    self.paused = true;
+  }
+
+  pub fn resume(&mut self) {
+    self.paused = false;
+  }
 
+  pub fn is_paused(&self) -> bool {
+    self.paused
+  }
+
+  pub fn speed(&self) -> f64 {
+    self.config.speed
+  }
+
+  pub fn is_practice(&self) -> bool {
+    self.config.practice
+  }
+
+  // 譜面上の time に移り、そこから先のノーツを打ち直せるようにする
+  #[must_use]
+  pub fn seek(&mut self, time: Seconds) -> Vec<MusicalTyperEvent> {
+    let prev_section_index = self.activity.current_section_index();
+    self.accumulated_time = time.max(0.0.into()) + self.offset;
+    self.activity.seek(self.score_time());
+
+    let mut events = vec![Seeked(self.accumulated_time)];
+    if prev_section_index != self.activity.current_section_index() {
+      events.push(EnterSection(self.section_name().into()));
+    }
     [self.pack_events(), events].concat()
   }
 
+  // 今のノーツから数えて count 個先 (負なら前) のノーツの頭に移る
+  #[must_use]
+  pub fn seek_note_by(
+    &mut self,
+    count: isize,
+  ) -> Vec<MusicalTyperEvent> {
+    let starts = self.activity.note_starts();
+    let current = self.activity.current_position();
+    let index = starts
+      .iter()
+      .position(|(position, _)| *position == current)
+      .unwrap_or(0) as isize;
+    let target = (index + count).clamp(0, starts.len() as isize - 1);
+    match starts.get(target as usize) {
+      Some(&(_, start)) => self.seek(start),
+      None => vec![],
+    }
+  }
+
+  // 名前の節の頭に移る
+  #[must_use]
+  pub fn seek_section(
+    &mut self,
+    name: &str,
+  ) -> Vec<MusicalTyperEvent> {
+    let start = self
+      .activity
+      .sections()
+      .find(|section| section.name() == name)
+      .map(|section| section.duration().start());
+    match start {
+      Some(start) => self.seek(start),
+      None => vec![],
+    }
+  }
+
+  // 今の節から数えて count 個先 (負なら前) の節の頭に移る
+  #[must_use]
+  pub fn seek_section_by(
+    &mut self,
+    count: isize,
+  ) -> Vec<MusicalTyperEvent> {
+    let index = (self.activity.current_section_index() as isize
+      + count)
+      .max(0) as usize;
+    let name = self
+      .activity
+      .sections()
+      .nth(index)
+      .map(|section| section.name().to_owned());
+    match name {
+      Some(name) => self.seek_section(&name),
+      None => vec![],
+    }
+  }
+
+  // 譜面上の from から to までを繰り返す
+  pub fn set_loop(&mut self, from: Seconds, to: Seconds) {
+    self.loop_range = Some((from.min(to), from.max(to)));
+  }
+
+  // 今の節を繰り返す
+  pub fn loop_current_section(&mut self) {
+    if let Some(duration) = self
+      .activity
+      .current_section()
+      .map(|section| section.duration().clone())
+    {
+      self.set_loop(duration.start(), duration.end());
+    }
+  }
+
+  pub fn clear_loop(&mut self) {
+    self.loop_range = None;
+  }
+
+  pub fn loop_range(&self) -> Option<(Seconds, Seconds)> {
+    self.loop_range
+  }
+
+  pub fn replay(&self, score_hash: u64) -> Replay {
+    let mut replay = self.replay.clone();
+    replay.score_hash = score_hash;
+    replay.set_score_point(self.activity.score().score_point);
+    replay
+  }
+
+  // 打った打ち方を覚えて、次からの表示に使う
+  fn record_spellings(&mut self, sentence: &Sentence) {
+    for (kana, style) in sentence.spellings() {
+      self.config.spelling_profile.record(&kana, &style);
+    }
+  }
+
   fn pack_events(&mut self) -> Vec<MusicalTyperEvent> {
     let sentence = self.activity.current_sentence();
     self.event_queue.push(UpdateSentence(sentence));
@@ -220,8 +552,39 @@ impl MusicalTyper {
     self.accumulated_time
   }
 
+  // 譜面上の時間。曲の頭から offset までは最初のノーツに留める
+  fn score_time(&self) -> Seconds {
+    (self.accumulated_time - self.offset).max(0.0.into())
+  }
+
   pub fn section_remaining_ratio(&self) -> f64 {
-    self.activity.remaining_ratio(self.accumulated_time)
+    self.activity.remaining_ratio(self.score_time())
+  }
+
+  pub fn section_name(&self) -> &str {
+    self
+      .activity
+      .current_section()
+      .map_or("", |section| section.name())
+  }
+
+  pub fn input_scheme(&self) -> &InputScheme {
+    &self.config.input_scheme
+  }
+
+  pub fn scoring(&self) -> &ScoringProfile {
+    &self.config.scoring
+  }
+
+  pub fn spelling_profile(&self) -> &SpellingProfile {
+    &self.config.spelling_profile
+  }
+
+  // これまでのものに、今回の遊びでの打鍵を足したもの
+  pub fn key_profile(&self) -> KeyProfile {
+    let mut profile = self.config.key_profile.clone();
+    profile.merge(&KeyProfile::from_logs(&self.activity.note_logs()));
+    profile
   }
 
   pub fn music_info(&self) -> MusicInfo {
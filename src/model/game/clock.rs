@@ -0,0 +1,64 @@
+use super::super::exp::time::Seconds;
+use std::time::Instant;
+
+// ゲームを進める時計。遊び始めてからの時間を返す
+pub trait Clock {
+  fn now(&mut self) -> Seconds;
+}
+
+// 実際の時間で進む時計
+pub struct SystemClock {
+  started: Instant,
+  // started の時点で指していた時間
+  base: Seconds,
+}
+
+impl SystemClock {
+  pub fn new() -> Self {
+    Self {
+      started: Instant::now(),
+      base: 0.0.into(),
+    }
+  }
+
+  // 今を now として、そこから進め直す
+  pub fn set(&mut self, now: Seconds) {
+    self.started = Instant::now();
+    self.base = now;
+  }
+}
+
+impl Default for SystemClock {
+  fn default() -> Self {
+    Self::new()
+  }
+}
+
+impl Clock for SystemClock {
+  fn now(&mut self) -> Seconds {
+    self.base + self.started.elapsed().as_secs_f64().into()
+  }
+}
+
+// 読むたびに一定の間隔だけ進む時計
+pub struct StepClock {
+  step: Seconds,
+  now: Seconds,
+}
+
+impl StepClock {
+  pub fn new(step: Seconds) -> Self {
+    Self {
+      step,
+      now: 0.0.into(),
+    }
+  }
+}
+
+impl Clock for StepClock {
+  fn now(&mut self) -> Seconds {
+    let now = self.now;
+    self.now += self.step;
+    now
+  }
+}
@@ -0,0 +1,89 @@
+use super::{
+  super::exp::{scoremap::Scoremap, time::Seconds},
+  run_replay, MusicalTyper, MusicalTyperConfig, MusicalTyperError,
+  Replay, ReplayCursor,
+};
+
+// 自分と比べたゴーストの様子
+#[derive(Debug, Clone, PartialEq)]
+pub struct GhostStatus {
+  // 正しく打ったキーの数と点数の差。正なら自分が先行している
+  pub type_lead: i64,
+  pub point_lead: i32,
+  // 同じ文を打っていれば、ゴーストが打ち終えたローマ字の数
+  pub inputted_in_sentence: Option<usize>,
+}
+
+// 過去の遊びを自分のゲームと並べて進める
+pub struct Ghost {
+  model: MusicalTyper,
+  cursor: ReplayCursor,
+}
+
+impl Ghost {
+  pub fn new(
+    score: Scoremap,
+    replay: Replay,
+  ) -> Result<Self, MusicalTyperError> {
+    Ok(Self {
+      model: MusicalTyper::new(score, replay.config())?,
+      cursor: ReplayCursor::new(replay),
+    })
+  }
+
+  // 自分と同じ時計が now を指すところまで進める
+  pub fn catch_up(&mut self, now: Seconds) {
+    let _ = self.model.feed(self.cursor.until(now));
+  }
+
+  pub fn status(&self, player: &MusicalTyper) -> GhostStatus {
+    let mine = player.activity().score();
+    let ghost = self.model.activity().score();
+    let same_sentence = player.activity().current_position()
+      == self.model.activity().current_position();
+    GhostStatus {
+      type_lead: mine.correct_type_count() as i64
+        - ghost.correct_type_count() as i64,
+      point_lead: mine.score_point - ghost.score_point,
+      inputted_in_sentence: Some(
+        self
+          .model
+          .activity()
+          .current_sentence()
+          .roman()
+          .inputted
+          .len(),
+      )
+      .filter(|_| same_sentence),
+    }
+  }
+}
+
+// 同じ譜面のリプレイのうち、最も点数の高いもの
+pub fn best_replay(
+  score: &Scoremap,
+  score_hash: u64,
+  config: &MusicalTyperConfig,
+  replays: impl IntoIterator<Item = Replay>,
+) -> Option<Replay> {
+  replays
+    .into_iter()
+    .filter(|replay| replay.score_hash == score_hash)
+    // ローマ字表が変わっていると、同じ打鍵でも同じ遊びにならない
+    .filter(|replay| replay.check_table().is_ok())
+    // 点数の付け方や曲の速さが違う記録とは点数を比べられない
+    .filter(|replay| replay.scoring().name == config.scoring.name)
+    .filter(|replay| replay.speed() == config.speed)
+    .filter_map(|replay| {
+      // 点数を残していない古い記録だけ再生して確かめる
+      let point = match replay.score_point() {
+        Some(point) => point,
+        None => {
+          run_replay(score.clone(), &replay).ok()?.score.score_point
+        }
+      };
+      Some((point, replay))
+    })
+    .max_by_key(|(point, _)| *point)
+    .map(|(_, replay)| replay)
+}
@@ -0,0 +1,116 @@
+use super::{
+  super::exp::{
+    game_activity::GameScore, scoremap::Scoremap, time::Seconds,
+  },
+  Clock, MusicalTyper, MusicalTyperConfig, MusicalTyperError,
+  MusicalTyperEvent, Replay, ReplayEntry,
+};
+
+// 時計がこの回数続けて進まなければ、それ以上遊べないとみなす
+const STALLED_LIMIT: u32 = 1000;
+
+// 遊び始めてから time の時点で打ったキー
+#[derive(Debug, Clone, PartialEq)]
+pub struct KeyEvent {
+  pub time: Seconds,
+  pub key: char,
+}
+
+#[derive(Clone, PartialEq)]
+pub struct HeadlessLog {
+  // 起きた時点と出来事
+  pub events: Vec<(Seconds, MusicalTyperEvent)>,
+  pub score: GameScore,
+}
+
+// 画面や音を使わずに、時計と打鍵の記録だけで譜面を最後まで遊ぶ
+// 打鍵は時間順に並べておく。時計が進むたびにその時点までの打鍵を処理する
+// 時計が STALLED_LIMIT 回続けて進まなかったときは、そこまでの記録を返す
+pub fn run_headless(
+  score: Scoremap,
+  config: MusicalTyperConfig,
+  clock: &mut impl Clock,
+  keys: impl IntoIterator<Item = KeyEvent>,
+) -> Result<HeadlessLog, MusicalTyperError> {
+  // 曲の始まりから譜面の終わりまでの時間
+  let end: Seconds = {
+    let last_note_end = score
+      .sections
+      .iter()
+      .filter_map(|section| section.iter().last())
+      .last()
+      .map_or(0.0, |note| note.duration().end().as_f64());
+    ((last_note_end + score.metadata.offset.as_f64()) / config.speed)
+      .into()
+  };
+  let mut game = MusicalTyper::new(score, config)?;
+  let mut keys = keys.into_iter().peekable();
+  let mut log = vec![];
+  let mut record = |game: &MusicalTyper, events: Vec<_>| {
+    let now = game.accumulated_time();
+    log.extend(events.into_iter().map(|event| (now, event)));
+  };
+
+  let mut ended = false;
+  let mut last_now = None;
+  let mut stalled = 0;
+  while !ended {
+    let now = clock.now();
+    if last_now.is_some_and(|last: Seconds| {
+      now.as_millis() <= last.as_millis()
+    }) {
+      stalled += 1;
+      if STALLED_LIMIT <= stalled {
+        break;
+      }
+    } else {
+      stalled = 0;
+    }
+    last_now = Some(now);
+    while let Some(key) = keys.next_if(|key| key.time <= now) {
+      let events = game.elapse_time(
+        (key.time - game.accumulated_time()).max(0.0.into()),
+      );
+      ended = events.contains(&MusicalTyperEvent::EndOfScore);
+      record(&game, events);
+      if ended {
+        break;
+      }
+      let events = game.key_press(Some(key.key).into_iter());
+      record(&game, events);
+    }
+    if !ended {
+      let events = game
+        .elapse_time((now - game.accumulated_time()).max(0.0.into()));
+      ended = events.contains(&MusicalTyperEvent::EndOfScore);
+      record(&game, events);
+    }
+    // 打鍵を使い切って譜面の終わりも過ぎたなら、それ以上進めても何も起きない
+    if keys.peek().is_none() && end < now {
+      break;
+    }
+  }
+
+  Ok(HeadlessLog {
+    events: log,
+    score: game.activity().score().clone(),
+  })
+}
+
+// リプレイを画面なしで再生する。記録したときと同じ結果になる
+pub fn run_replay(
+  score: Scoremap,
+  replay: &Replay,
+) -> Result<HeadlessLog, MusicalTyperError> {
+  let mut game = MusicalTyper::new(score, replay.config())?;
+  let mut log = vec![];
+  for batch in replay.entries.chunk_by(ReplayEntry::same_batch) {
+    let events = game.feed(batch);
+    let now = game.accumulated_time();
+    log.extend(events.into_iter().map(|event| (now, event)));
+  }
+  Ok(HeadlessLog {
+    events: log,
+    score: game.activity().score().clone(),
+  })
+}
@@ -0,0 +1,323 @@
+use super::super::exp::{
+  note::{Keystroke, NoteLog},
+  time::Seconds,
+};
+use std::collections::BTreeMap;
+
+// 1 つのキーか読み仮名を打った回数と速さ
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct KeyRecord {
+  pub correct: u32,
+  pub mistaken: u32,
+  // 直前の打鍵からの時間の合計 (ミリ秒) と、それを測った回数
+  latency_millis: i64,
+  latency_count: u32,
+}
+
+impl KeyRecord {
+  pub fn error_rate(&self) -> f64 {
+    if self.correct + self.mistaken == 0 {
+      return 0.0;
+    }
+    self.mistaken as f64 / (self.correct + self.mistaken) as f64
+  }
+
+  pub fn average_latency(&self) -> Option<Seconds> {
+    if self.latency_count == 0 {
+      return None;
+    }
+    let millis = self.latency_millis / self.latency_count as i64;
+    Some((millis as f64 / 1000.0).into())
+  }
+
+  fn add(&mut self, other: &KeyRecord) {
+    self.correct += other.correct;
+    self.mistaken += other.mistaken;
+    self.latency_millis += other.latency_millis;
+    self.latency_count += other.latency_count;
+  }
+
+  fn point(&mut self, succeed: bool, latency: Option<Seconds>) {
+    if succeed {
+      self.correct += 1;
+    } else {
+      self.mistaken += 1;
+    }
+    if let Some(latency) = latency {
+      self.latency_millis += latency.as_millis();
+      self.latency_count += 1;
+    }
+  }
+
+  // 間違えやすいほど、間違えやすさが同じなら遅いほど先
+  fn weaker_than(&self, other: &KeyRecord) -> std::cmp::Ordering {
+    let latency = |record: &KeyRecord| {
+      record.average_latency().map_or(0, |time| time.as_millis())
+    };
+    other
+      .error_rate()
+      .partial_cmp(&self.error_rate())
+      .unwrap_or(std::cmp::Ordering::Equal)
+      .then(latency(other).cmp(&latency(self)))
+  }
+}
+
+// キーごと、読み仮名ごとの打ち間違えと速さ。遊ぶたびに足していく
+#[derive(Debug, Default, Clone, PartialEq)]
+pub struct KeyProfile {
+  keys: BTreeMap<char, KeyRecord>,
+  kana: BTreeMap<String, KeyRecord>,
+  // 打つはずだったキーと、代わりに打ったキーの回数
+  confusion: BTreeMap<(char, char), u32>,
+}
+
+impl KeyProfile {
+  pub fn new() -> Self {
+    Self::default()
+  }
+
+  // 1 回の遊びでの、文ごとの記録から集める
+  pub fn from_logs(logs: &[NoteLog]) -> Self {
+    let mut profile = Self::new();
+    for log in logs {
+      profile.record(&log.keystrokes);
+    }
+    profile
+  }
+
+  // 1 つの文での打鍵を順に足す。正しい打鍵は打ったキーに、間違いは打つはずだったキーに数える
+  pub fn record(&mut self, keystrokes: &[Keystroke]) {
+    let mut prev_time = None;
+    for keystroke in keystrokes {
+      let latency = prev_time.map(|prev| keystroke.time - prev);
+      prev_time = Some(keystroke.time);
+      let key = if keystroke.succeed {
+        Some(keystroke.typed)
+      } else {
+        keystroke.expected
+      };
+      if let Some(key) = key {
+        self
+          .keys
+          .entry(key)
+          .or_default()
+          .point(keystroke.succeed, latency);
+      }
+      if let Some(kana) = &keystroke.kana {
+        self
+          .kana
+          .entry(kana.clone())
+          .or_default()
+          .point(keystroke.succeed, latency);
+      }
+      if let (false, Some(expected)) =
+        (keystroke.succeed, keystroke.expected)
+      {
+        *self
+          .confusion
+          .entry((expected, keystroke.typed))
+          .or_default() += 1;
+      }
+    }
+  }
+
+  pub fn merge(&mut self, other: &KeyProfile) {
+    for (key, record) in &other.keys {
+      self.keys.entry(*key).or_default().add(record);
+    }
+    for (kana, record) in &other.kana {
+      self.kana.entry(kana.clone()).or_default().add(record);
+    }
+    for (pair, count) in &other.confusion {
+      *self.confusion.entry(*pair).or_default() += count;
+    }
+  }
+
+  pub fn key(&self, key: char) -> Option<&KeyRecord> {
+    self.keys.get(&key)
+  }
+
+  pub fn kana(&self, kana: &str) -> Option<&KeyRecord> {
+    self.kana.get(kana)
+  }
+
+  // expected を打つはずが typed を打った回数
+  pub fn confusion(&self, expected: char, typed: char) -> u32 {
+    self.confusion.get(&(expected, typed)).copied().unwrap_or(0)
+  }
+
+  // expected の代わりによく打ってしまうキーを多い順に
+  pub fn confused_with(&self, expected: char) -> Vec<(char, u32)> {
+    let mut typed: Vec<_> = self
+      .confusion
+      .iter()
+      .filter(|((from, _), _)| *from == expected)
+      .map(|((_, typed), count)| (*typed, *count))
+      .collect();
+    typed.sort_by_key(|&(_, count)| std::cmp::Reverse(count));
+    typed
+  }
+
+  // 苦手なキーを苦手な順に。min_count 回より少なく打ったキーは数えない
+  pub fn weakest_keys(
+    &self,
+    min_count: u32,
+  ) -> Vec<(char, KeyRecord)> {
+    rank(&self.keys, min_count)
+  }
+
+  pub fn weakest_kana(
+    &self,
+    min_count: u32,
+  ) -> Vec<(String, KeyRecord)> {
+    rank(&self.kana, min_count)
+  }
+
+  // `key<TAB>キー<TAB>正しく打った回数<TAB>間違えた回数<TAB>時間の合計<TAB>測った回数`
+  // `kana` も同じ形で、`confusion<TAB>打つはずだったキー<TAB>打ったキー<TAB>回数`
+  // 読めない行は飛ばす
+  pub fn parse(source: &str) -> Self {
+    let mut profile = Self::new();
+    for line in source.lines() {
+      if line.trim().is_empty() || line.starts_with('#') {
+        continue;
+      }
+      let columns: Vec<_> = line.split('\t').collect();
+      let numbers: Option<Vec<i64>> =
+        columns.iter().skip(2).map(|n| n.parse().ok()).collect();
+      let single = |column: &str| {
+        let mut chars = column.chars();
+        chars.next().filter(|_| chars.next().is_none())
+      };
+      match (columns.as_slice(), numbers.as_deref()) {
+        (
+          ["key", key, ..],
+          Some(&[correct, mistaken, latency_millis, latency_count]),
+        ) => {
+          if let Some(key) = single(key) {
+            profile.keys.insert(
+              key,
+              KeyRecord {
+                correct: correct as u32,
+                mistaken: mistaken as u32,
+                latency_millis,
+                latency_count: latency_count as u32,
+              },
+            );
+          }
+        }
+        (
+          ["kana", kana, ..],
+          Some(&[correct, mistaken, latency_millis, latency_count]),
+        ) => {
+          profile.kana.insert(
+            kana.to_string(),
+            KeyRecord {
+              correct: correct as u32,
+              mistaken: mistaken as u32,
+              latency_millis,
+              latency_count: latency_count as u32,
+            },
+          );
+        }
+        (["confusion", expected, typed, count], _) => {
+          if let (Some(expected), Some(typed), Ok(count)) =
+            (single(expected), single(typed), count.parse())
+          {
+            profile.confusion.insert((expected, typed), count);
+          }
+        }
+        _ => {}
+      }
+    }
+    profile
+  }
+
+  pub fn to_tsv(&self) -> String {
+    let record_line = |kind: &str, key: &str, record: &KeyRecord| {
+      format!(
+        "{}\t{}\t{}\t{}\t{}\t{}\n",
+        kind,
+        key,
+        record.correct,
+        record.mistaken,
+        record.latency_millis,
+        record.latency_count
+      )
+    };
+    let keys = self.keys.iter().map(|(key, record)| {
+      record_line("key", &key.to_string(), record)
+    });
+    let kana = self
+      .kana
+      .iter()
+      .map(|(kana, record)| record_line("kana", kana, record));
+    let confusion =
+      self.confusion.iter().map(|((expected, typed), count)| {
+        format!("confusion\t{}\t{}\t{}\n", expected, typed, count)
+      });
+    keys.chain(kana).chain(confusion).collect()
+  }
+}
+
+fn rank<K: Clone>(
+  records: &BTreeMap<K, KeyRecord>,
+  min_count: u32,
+) -> Vec<(K, KeyRecord)> {
+  let mut ranked: Vec<_> = records
+    .iter()
+    .filter(|(_, record)| {
+      min_count <= record.correct + record.mistaken
+    })
+    .map(|(key, record)| (key.clone(), *record))
+    .collect();
+  ranked.sort_by(|(_, a), (_, b)| a.weaker_than(b));
+  ranked
+}
+
+#[test]
+fn weak_keys() {
+  let stroke = |millis: i64, typed, expected, kana: &str| Keystroke {
+    time: (millis as f64 / 1000.0).into(),
+    typed,
+    expected: Some(expected),
+    kana: Some(kana.into()),
+    succeed: typed == expected,
+  };
+  // 「かた」を k a r t a と打った
+  let mut profile = KeyProfile::new();
+  profile.record(&[
+    stroke(100, 'k', 'k', "か"),
+    stroke(300, 'a', 'a', "か"),
+    stroke(400, 'r', 't', "た"),
+    stroke(900, 't', 't', "た"),
+    stroke(1000, 'a', 'a', "た"),
+  ]);
+  assert_eq!(profile.confusion('t', 'r'), 1);
+  assert_eq!(profile.confused_with('t'), [('r', 1)]);
+  let t = profile.key('t').unwrap();
+  assert_eq!((t.correct, t.mistaken), (1, 1));
+  assert_eq!(t.error_rate(), 0.5);
+  assert_eq!(
+    t.average_latency().map(|time| time.as_millis()),
+    Some(300)
+  );
+  // 最初の打鍵は直前がないので速さを測らない
+  assert_eq!(profile.key('k').unwrap().average_latency(), None);
+
+  let keys: Vec<_> = profile
+    .weakest_keys(1)
+    .into_iter()
+    .map(|(key, _)| key)
+    .collect();
+  assert_eq!(keys, ['t', 'a', 'k']);
+  assert_eq!(profile.weakest_keys(2).len(), 2);
+  assert_eq!(profile.weakest_kana(1)[0].0, "た");
+
+  // 遊ぶたびに足し、書き出して読み直せる
+  let mut total = KeyProfile::parse(&profile.to_tsv());
+  assert_eq!(total, profile);
+  total.merge(&profile);
+  assert_eq!(total.confusion('t', 'r'), 2);
+  assert_eq!(total.kana("た").unwrap().mistaken, 2);
+}
@@ -0,0 +1,457 @@
+use super::{
+  super::exp::{
+    game_activity::LifeRules,
+    sentence::{
+      latin::LatinOptions,
+      roman::{InputScheme, SpellingProfile},
+    },
+    time::Seconds,
+  },
+  MusicalTyperConfig, ScoringProfile, ScoringProfileError,
+};
+use std::fmt::{Display, Formatter, Result};
+
+const HEADER: &str = "# musical typer replay";
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayError {
+  InvalidLine {
+    line_num: usize,
+    reason: &'static str,
+  },
+  // 譜面が記録したときと違う
+  ScoreMismatch,
+  // ローマ字表か入力方式が記録したときと違う
+  TableMismatch,
+  ScoringError(ScoringProfileError),
+}
+
+impl Display for ReplayError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    match self {
+      ReplayError::InvalidLine { line_num, reason } => {
+        write!(f, "{} 行目: {}", line_num, reason)
+      }
+      ReplayError::ScoreMismatch => {
+        write!(f, "譜面が記録したときと異なります。")
+      }
+      ReplayError::TableMismatch => {
+        write!(f, "ローマ字表が記録したときと異なります。")
+      }
+      ReplayError::ScoringError(err) => {
+        write!(f, "点数の付け方が読めません。{}", err)
+      }
+    }
+  }
+}
+
+// 譜面ファイルの内容から求める値。リプレイと譜面の対応を確かめる
+pub fn score_hash(source: &str) -> u64 {
+  // FNV-1a
+  source.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+    (hash ^ byte as u64).wrapping_mul(0x100_0000_01b3)
+  })
+}
+
+// 入力方式が使うローマ字表から求める値。手元の romaji.tsv で表が変わっていないか確かめる
+fn table_hash(input_scheme: &InputScheme) -> u64 {
+  score_hash(&input_scheme.table().canonical_text())
+}
+
+// 曲の始まりからの時間と、その時点でゲームに与えたもの
+#[derive(Debug, Clone, PartialEq)]
+pub enum ReplayEntry {
+  // ここまで時間を進めた
+  Tick(Seconds),
+  Key(Seconds, char),
+}
+
+impl ReplayEntry {
+  pub fn time(&self) -> Seconds {
+    match self {
+      ReplayEntry::Tick(time) | ReplayEntry::Key(time, _) => *time,
+    }
+  }
+
+  // 同じ時点の打鍵は 1 度にまとめて打ったもの
+  pub fn same_batch(&self, other: &Self) -> bool {
+    match (self, other) {
+      (ReplayEntry::Key(time, _), ReplayEntry::Key(other, _)) => {
+        time.as_millis() == other.as_millis()
+      }
+      _ => false,
+    }
+  }
+}
+
+// 1 回の遊びを再現するための記録
+#[derive(Debug, Clone)]
+pub struct Replay {
+  pub score_hash: u64,
+  input_scheme: InputScheme,
+  // 表を記録する前のリプレイにはない
+  table_hash: Option<u64>,
+  latin_options: LatinOptions,
+  trailing_n: bool,
+  spelling_profile: SpellingProfile,
+  scoring: ScoringProfile,
+  life_gauge: Option<LifeRules>,
+  speed: f64,
+  // 遊び終えたときの点数。ゴーストを選ぶたびに全てを再生し直さなくて済む
+  score_point: Option<i32>,
+  pub entries: Vec<ReplayEntry>,
+}
+
+impl Replay {
+  pub fn new(
+    score_hash: u64,
+    config: &MusicalTyperConfig,
+    entries: Vec<ReplayEntry>,
+  ) -> Self {
+    Self {
+      score_hash,
+      input_scheme: config.input_scheme.clone(),
+      table_hash: Some(table_hash(&config.input_scheme)),
+      latin_options: config.latin_options,
+      trailing_n: config.trailing_n,
+      spelling_profile: config.spelling_profile.clone(),
+      scoring: config.scoring.clone(),
+      life_gauge: config.life_gauge.clone(),
+      speed: config.speed,
+      score_point: None,
+      entries,
+    }
+  }
+
+  // 記録したときと同じ設定
+  pub fn config(&self) -> MusicalTyperConfig {
+    let config = match &self.life_gauge {
+      Some(rules) => {
+        MusicalTyperConfig::default().life_gauge(rules.clone())
+      }
+      None => MusicalTyperConfig::default(),
+    };
+    config
+      .input_scheme(self.input_scheme.clone())
+      .latin_options(self.latin_options)
+      .trailing_n(self.trailing_n)
+      .spelling_profile(self.spelling_profile.clone())
+      .scoring(self.scoring.clone())
+      .speed(self.speed)
+  }
+
+  pub fn speed(&self) -> f64 {
+    self.speed
+  }
+
+  pub fn score_point(&self) -> Option<i32> {
+    self.score_point
+  }
+
+  pub fn set_score_point(&mut self, score_point: i32) {
+    self.score_point = Some(score_point);
+  }
+
+  // 記録したときの点数の付け方
+  pub fn scoring(&self) -> &ScoringProfile {
+    &self.scoring
+  }
+
+  pub fn check_score(
+    &self,
+    source: &str,
+  ) -> std::result::Result<(), ReplayError> {
+    if self.score_hash == score_hash(source) {
+      Ok(())
+    } else {
+      Err(ReplayError::ScoreMismatch)
+    }
+  }
+
+  // 記録したときと今とで、入力方式の表が同じか
+  pub fn check_table(&self) -> std::result::Result<(), ReplayError> {
+    match self.table_hash {
+      Some(hash) if hash != table_hash(&self.input_scheme) => {
+        Err(ReplayError::TableMismatch)
+      }
+      _ => Ok(()),
+    }
+  }
+
+  pub fn parse(
+    source: &str,
+  ) -> std::result::Result<Self, ReplayError> {
+    let invalid = |line_num, reason| ReplayError::InvalidLine {
+      line_num,
+      reason,
+    };
+    let mut lines = source.lines().enumerate();
+    if lines.next().map(|(_, line)| line) != Some(HEADER) {
+      return Err(invalid(1, "リプレイファイルではありません。"));
+    }
+    let mut replay =
+      Replay::new(0, &MusicalTyperConfig::default(), vec![]);
+    replay.table_hash = None;
+    let mut profile = String::new();
+    let mut scoring = String::new();
+    for (line_num, line) in lines {
+      let line_num = line_num + 1;
+      let columns: Vec<_> = line.splitn(3, '\t').collect();
+      let time = || -> std::result::Result<Seconds, ReplayError> {
+        columns
+          .get(1)
+          .and_then(|millis| millis.parse::<i64>().ok())
+          .map(|millis| (millis as f64 / 1000.0).into())
+          .ok_or(invalid(line_num, "時間が読めません。"))
+      };
+      let flag = || match columns.get(1) {
+        Some(&"true") => Ok(true),
+        Some(&"false") => Ok(false),
+        _ => Err(invalid(line_num, "true か false を書きます。")),
+      };
+      match columns[0] {
+        "score" => {
+          replay.score_hash = columns
+            .get(1)
+            .and_then(|hash| u64::from_str_radix(hash, 16).ok())
+            .ok_or(invalid(line_num, "譜面の値が読めません。"))?;
+        }
+        "scheme" => {
+          replay.input_scheme = columns
+            .get(1)
+            .and_then(|name| InputScheme::from_name(name))
+            .ok_or(invalid(line_num, "再生できない入力方式です。"))?;
+        }
+        "table" => {
+          replay.table_hash = Some(
+            columns
+              .get(1)
+              .and_then(|hash| u64::from_str_radix(hash, 16).ok())
+              .ok_or(invalid(
+                line_num,
+                "ローマ字表の値が読めません。",
+              ))?,
+          );
+        }
+        "case_sensitive" => {
+          replay.latin_options.case_sensitive = flag()?
+        }
+        "strict_punctuation" => {
+          replay.latin_options.strict_punctuation = flag()?
+        }
+        "trailing_n" => replay.trailing_n = flag()?,
+        "speed" => {
+          replay.speed = columns
+            .get(1)
+            .and_then(|speed| speed.parse().ok())
+            .ok_or(invalid(line_num, "速さが読めません。"))?;
+        }
+        "point" => {
+          replay.score_point = Some(
+            columns
+              .get(1)
+              .and_then(|point| point.parse().ok())
+              .ok_or(invalid(line_num, "点数が読めません。"))?,
+          );
+        }
+        "spelling" => {
+          profile.push_str(columns[1..].join("\t").as_str());
+          profile.push('\n');
+        }
+        "scoring" => {
+          scoring.push_str(columns[1..].join("\t").as_str());
+          scoring.push('\n');
+        }
+        "life" => {
+          let amounts = columns[1..]
+            .join("\t")
+            .split('\t')
+            .map(str::parse::<f64>)
+            .collect::<std::result::Result<Vec<_>, _>>();
+          replay.life_gauge = match amounts.as_deref() {
+            Ok(
+              &[max, wrong_type, missed_sentence, complete_sentence, perfect_sentence],
+            ) => Some(LifeRules {
+              max,
+              wrong_type,
+              missed_sentence,
+              complete_sentence,
+              perfect_sentence,
+            }),
+            _ => {
+              return Err(invalid(
+                line_num,
+                "ライフゲージの量が読めません。",
+              ))
+            }
+          };
+        }
+        "tick" => replay.entries.push(ReplayEntry::Tick(time()?)),
+        "key" => {
+          let key = columns
+            .get(2)
+            .and_then(|key| key.chars().next())
+            .ok_or(invalid(line_num, "キーがありません。"))?;
+          replay.entries.push(ReplayEntry::Key(time()?, key));
+        }
+        "" => {}
+        _ => return Err(invalid(line_num, "不明な行です。")),
+      }
+    }
+    replay.spelling_profile = SpellingProfile::parse(&profile);
+    // 点数の付け方のない記録は既定のもので遊んだもの
+    if let Some(scoring) = ScoringProfile::parse_all(&scoring)
+      .map_err(ReplayError::ScoringError)?
+      .pop()
+    {
+      replay.scoring = scoring;
+    }
+    Ok(replay)
+  }
+
+  pub fn to_text(&self) -> String {
+    let mut lines = vec![
+      HEADER.to_owned(),
+      format!("score\t{:016x}", self.score_hash),
+      format!("scheme\t{}", self.input_scheme.name()),
+    ];
+    if let Some(hash) = self.table_hash {
+      lines.push(format!("table\t{:016x}", hash));
+    }
+    lines.extend(vec![
+      format!(
+        "case_sensitive\t{}",
+        self.latin_options.case_sensitive
+      ),
+      format!(
+        "strict_punctuation\t{}",
+        self.latin_options.strict_punctuation
+      ),
+      format!("trailing_n\t{}", self.trailing_n),
+      format!("speed\t{}", self.speed),
+    ]);
+    lines.extend(
+      self
+        .spelling_profile
+        .to_tsv()
+        .lines()
+        .map(|line| format!("spelling\t{}", line)),
+    );
+    lines.extend(
+      self
+        .scoring
+        .to_text()
+        .lines()
+        .map(|line| format!("scoring\t{}", line)),
+    );
+    if let Some(point) = self.score_point {
+      lines.push(format!("point\t{}", point));
+    }
+    if let Some(rules) = &self.life_gauge {
+      lines.push(format!(
+        "life\t{}\t{}\t{}\t{}\t{}",
+        rules.max,
+        rules.wrong_type,
+        rules.missed_sentence,
+        rules.complete_sentence,
+        rules.perfect_sentence
+      ));
+    }
+    lines.extend(self.entries.iter().map(|entry| match entry {
+      ReplayEntry::Tick(time) => {
+        format!("tick\t{}", time.as_millis())
+      }
+      ReplayEntry::Key(time, key) => {
+        format!("key\t{}\t{}", time.as_millis(), key)
+      }
+    }));
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+  }
+}
+
+// リプレイの記録を時計に合わせて順に取り出す
+pub struct ReplayCursor {
+  replay: Replay,
+  played: usize,
+}
+
+impl ReplayCursor {
+  pub fn new(replay: Replay) -> Self {
+    Self { replay, played: 0 }
+  }
+
+  // まだ取り出しておらず、now までに起きた記録
+  pub fn until(&mut self, now: Seconds) -> &[ReplayEntry] {
+    let rest = &self.replay.entries[self.played..];
+    let count = rest
+      .iter()
+      .position(|entry| now < entry.time())
+      .unwrap_or(rest.len());
+    self.played += count;
+    &rest[..count]
+  }
+
+  pub fn finished(&self) -> bool {
+    self.replay.entries.len() <= self.played
+  }
+}
+
+#[test]
+fn round_trip() {
+  let config = MusicalTyperConfig::default()
+    .input_scheme(InputScheme::Azik)
+    .trailing_n(true)
+    .life_gauge(Default::default())
+    .speed(0.75)
+    .scoring(ScoringProfile::bundled().remove(2))
+    .spelling_profile(SpellingProfile::parse(
+      "し\tshi\nち\tchi\t2\n",
+    ));
+  let mut replay = Replay::new(
+    score_hash("*1.0\n"),
+    &config,
+    vec![
+      ReplayEntry::Tick(0.016.into()),
+      ReplayEntry::Key(0.016.into(), ' '),
+      ReplayEntry::Key(0.016.into(), 'a'),
+      ReplayEntry::Tick(1.5.into()),
+    ],
+  );
+  replay.set_score_point(-120);
+  let parsed = Replay::parse(&replay.to_text()).unwrap();
+  assert_eq!(parsed.to_text(), replay.to_text());
+  assert_eq!(parsed.score_point(), Some(-120));
+  assert_eq!(parsed.scoring().name, "strict");
+  assert_eq!(parsed.life_gauge, Some(LifeRules::default()));
+  assert_eq!(parsed.speed(), 0.75);
+  assert_eq!(parsed.check_score("*1.0\n"), Ok(()));
+  assert_eq!(
+    parsed.check_score("*2.0\n"),
+    Err(ReplayError::ScoreMismatch)
+  );
+  assert_eq!(parsed.check_table(), Ok(()));
+
+  // 別のローマ字表で記録したものは再生しない
+  let text = replay.to_text();
+  let table_line = text
+    .lines()
+    .find(|line| line.starts_with("table\t"))
+    .unwrap();
+  let other_table =
+    text.replace(table_line, "table\t0000000000000000");
+  assert_eq!(
+    Replay::parse(&other_table).unwrap().check_table(),
+    Err(ReplayError::TableMismatch)
+  );
+  // 表を記録する前のリプレイは確かめようがないので再生する
+  let legacy = text.replace(&format!("{}\n", table_line), "");
+  assert_eq!(Replay::parse(&legacy).unwrap().check_table(), Ok(()));
+
+  assert_eq!(
+    Replay::parse("# musical typer replay\ntick\tx\n").unwrap_err(),
+    ReplayError::InvalidLine {
+      line_num: 2,
+      reason: "時間が読めません。",
+    }
+  );
+}
@@ -0,0 +1,262 @@
+use super::Point;
+use std::fmt::{Display, Formatter, Result};
+
+const BUNDLED: &str = include_str!("scoring.tsv");
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ScoringProfileError {
+  InvalidLine {
+    line_num: usize,
+    reason: &'static str,
+  },
+}
+
+impl Display for ScoringProfileError {
+  fn fmt(&self, f: &mut Formatter<'_>) -> Result {
+    match self {
+      ScoringProfileError::InvalidLine { line_num, reason } => {
+        write!(f, "{} 行目: {}", line_num, reason)
+      }
+    }
+  }
+}
+
+// 点数の付け方とランクの境目
+#[derive(Debug, Clone, PartialEq)]
+pub struct ScoringProfile {
+  pub name: String,
+  pub correct_type: Point,
+  pub wrong_type: Point,
+  pub missed_sentence: Point,
+  pub complete_sentence: Point,
+  pub perfect_sentence: Point,
+  pub perfect_section: Point,
+  // 下限の高い順
+  ranks: Vec<(f64, String)>,
+  // コンボ数と、そこから正しい打鍵の点に掛ける倍率。コンボ数の低い順
+  combo_tiers: Vec<(u32, f64)>,
+}
+
+impl Default for ScoringProfile {
+  fn default() -> Self {
+    Self::bundled()
+      .into_iter()
+      .next()
+      .expect("bundled scoring profile is empty")
+  }
+}
+
+impl ScoringProfile {
+  fn named(name: &str) -> Self {
+    Self {
+      name: name.into(),
+      correct_type: 0,
+      wrong_type: 0,
+      missed_sentence: 0,
+      complete_sentence: 0,
+      perfect_sentence: 0,
+      perfect_section: 0,
+      ranks: vec![],
+      combo_tiers: vec![],
+    }
+  }
+
+  // 同梱の付け方全て。最初のものが既定
+  pub fn bundled() -> Vec<Self> {
+    Self::parse_all(BUNDLED)
+      .expect("bundled scoring profile is invalid")
+  }
+
+  pub fn parse_all(
+    source: &str,
+  ) -> std::result::Result<Vec<Self>, ScoringProfileError> {
+    let mut profiles: Vec<Self> = vec![];
+    for (line_num, line) in source.lines().enumerate() {
+      let line_num = line_num + 1;
+      let invalid = |reason| ScoringProfileError::InvalidLine {
+        line_num,
+        reason,
+      };
+      let line = line.trim();
+      if line.is_empty() || line.starts_with('#') {
+        continue;
+      }
+      if let Some(name) = line
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+      {
+        profiles.push(Self::named(name));
+        continue;
+      }
+      let profile = profiles
+        .last_mut()
+        .ok_or(invalid("先に [名前] を書きます。"))?;
+      let columns: Vec<_> = line.split('\t').collect();
+      if let ["rank", lower, label] = columns.as_slice() {
+        let lower: f64 =
+          lower.parse().map_err(|_| invalid("下限が読めません。"))?;
+        if profile
+          .ranks
+          .last()
+          .is_some_and(|(last, _)| *last <= lower)
+        {
+          return Err(invalid("ランクは下限の高い順に書きます。"));
+        }
+        profile.ranks.push((lower, (*label).into()));
+        continue;
+      }
+      if let ["combo", combo, multiplier] = columns.as_slice() {
+        let combo: u32 = combo
+          .parse()
+          .map_err(|_| invalid("コンボ数が読めません。"))?;
+        let multiplier: f64 = multiplier
+          .parse()
+          .map_err(|_| invalid("倍率が読めません。"))?;
+        if profile
+          .combo_tiers
+          .last()
+          .is_some_and(|(last, _)| combo <= *last)
+        {
+          return Err(invalid(
+            "コンボはコンボ数の低い順に書きます。",
+          ));
+        }
+        profile.combo_tiers.push((combo, multiplier));
+        continue;
+      }
+      let (item, point) = match columns.as_slice() {
+        [item, point] => (
+          *item,
+          point.parse().map_err(|_| invalid("点が読めません。"))?,
+        ),
+        _ => return Err(invalid("項目と点を書きます。")),
+      };
+      *match item {
+        "correct_type" => &mut profile.correct_type,
+        "wrong_type" => &mut profile.wrong_type,
+        "missed_sentence" => &mut profile.missed_sentence,
+        "complete_sentence" => &mut profile.complete_sentence,
+        "perfect_sentence" => &mut profile.perfect_sentence,
+        "perfect_section" => &mut profile.perfect_section,
+        _ => return Err(invalid("不明な項目です。")),
+      } = point;
+    }
+    Ok(profiles)
+  }
+
+  pub fn to_text(&self) -> String {
+    let mut lines = vec![
+      format!("[{}]", self.name),
+      format!("correct_type\t{}", self.correct_type),
+      format!("wrong_type\t{}", self.wrong_type),
+      format!("missed_sentence\t{}", self.missed_sentence),
+      format!("complete_sentence\t{}", self.complete_sentence),
+      format!("perfect_sentence\t{}", self.perfect_sentence),
+      format!("perfect_section\t{}", self.perfect_section),
+    ];
+    lines.extend(
+      self
+        .ranks
+        .iter()
+        .map(|(lower, label)| format!("rank\t{}\t{}", lower, label)),
+    );
+    lines.extend(self.combo_tiers.iter().map(
+      |(combo, multiplier)| {
+        format!("combo\t{}\t{}", combo, multiplier)
+      },
+    ));
+    lines.iter().map(|line| format!("{}\n", line)).collect()
+  }
+
+  // combo 回目まで続けて正しく打ったときの 1 打の点
+  pub fn correct_type_point(&self, combo: u32) -> i32 {
+    let multiplier = self
+      .combo_tiers
+      .iter()
+      .rev()
+      .find(|(lower, _)| *lower <= combo)
+      .map_or(1.0, |(_, multiplier)| *multiplier);
+    (self.correct_type as f64 * multiplier).round() as i32
+  }
+
+  // 倍率が上がる区切りのコンボ数か
+  pub fn is_combo_milestone(&self, combo: u32) -> bool {
+    self.combo_tiers.iter().any(|(lower, _)| *lower == combo)
+  }
+
+  // 正解率を 200 倍した値に対するランク
+  pub fn rank(&self, acc: f64) -> &str {
+    self
+      .ranks
+      .iter()
+      .find(|(lower, _)| *lower <= acc)
+      .or_else(|| self.ranks.last())
+      .map_or("", |(_, label)| label.as_str())
+  }
+}
+
+#[test]
+fn test_rank() {
+  let profile = ScoringProfile::default();
+  let rank = |acc| profile.rank(acc);
+  assert_eq!("Wow", rank(256.0));
+  assert_eq!("Unexpected", rank(199.9));
+  assert_eq!("Very God", rank(149.9));
+  assert_eq!("God", rank(124.9));
+  assert_eq!("Pro", rank(99.9));
+  assert_eq!("Genius", rank(99.49));
+  assert_eq!("Geki-tsuyo", rank(98.9));
+  assert_eq!("tsuyotsuyo", rank(97.9));
+  assert_eq!("AAA", rank(96.9));
+  assert_eq!("AA", rank(93.9));
+  assert_eq!("A", rank(89.9));
+  assert_eq!("B", rank(79.9));
+  assert_eq!("C", rank(59.9));
+  assert_eq!("D", rank(39.9));
+  assert_eq!("E", rank(19.9));
+  assert_eq!("F", rank(0.0));
+}
+
+#[test]
+fn profiles() {
+  let bundled = ScoringProfile::bundled();
+  let names: Vec<_> = bundled
+    .iter()
+    .map(|profile| profile.name.as_str())
+    .collect();
+  assert_eq!(names, ["standard", "casual", "strict"]);
+  for profile in &bundled {
+    assert_eq!(
+      ScoringProfile::parse_all(&profile.to_text()),
+      Ok(vec![profile.clone()])
+    );
+  }
+  assert_eq!(bundled[1].rank(170.0), "A");
+  assert_eq!(bundled[0].correct_type_point(24), 10);
+  assert_eq!(bundled[0].correct_type_point(25), 12);
+  assert_eq!(bundled[0].correct_type_point(150), 20);
+  assert!(bundled[0].is_combo_milestone(50));
+  assert!(!bundled[0].is_combo_milestone(51));
+
+  assert_eq!(
+    ScoringProfile::parse_all("[x]\nrank\t10\tA\nrank\t20\tB\n"),
+    Err(ScoringProfileError::InvalidLine {
+      line_num: 3,
+      reason: "ランクは下限の高い順に書きます。",
+    })
+  );
+  assert_eq!(
+    ScoringProfile::parse_all("[x]\ncombo\t10\t2\ncombo\t10\t3\n"),
+    Err(ScoringProfileError::InvalidLine {
+      line_num: 3,
+      reason: "コンボはコンボ数の低い順に書きます。",
+    })
+  );
+  assert_eq!(
+    ScoringProfile::parse_all("wrong_type\t1\n"),
+    Err(ScoringProfileError::InvalidLine {
+      line_num: 1,
+      reason: "先に [名前] を書きます。",
+    })
+  );
+}
@@ -1,5 +1,8 @@
 use super::{
-  super::exp::{scoremap::Scoremap, sentence::Sentence},
+  super::exp::{
+    scoremap::Scoremap,
+    sentence::{roman::InputScheme, Sentence},
+  },
   MusicalTypeResult, MusicalTyper, MusicalTyperConfig,
   MusicalTyperError, MusicalTyperEvent,
 };
@@ -12,6 +15,7 @@ enum Input {
 use Input::*;
 
 #[test]
+#[allow(clippy::into_iter_on_ref)]
 fn op1() -> Result<(), MusicalTyperError> {
   let test_score = Scoremap::from_str(
     r#"
@@ -88,3 +92,914 @@ fn op1() -> Result<(), MusicalTyperError> {
 
   Ok(())
 }
+
+#[test]
+fn offset() -> Result<(), MusicalTyperError> {
+  let test_score = Scoremap::from_str(
+    r#"
+:song_data void.ogg
+:offset 00.500
+
+[start]
+*2.22
+打鍵テスト
+:だけんてすと
+
+*3.0
+[end]
+"#,
+    |config| config,
+  )?;
+
+  let mut game =
+    MusicalTyper::new(test_score, MusicalTyperConfig::default())?;
+
+  use MusicalTyperEvent::*;
+  // 前奏の間も打鍵できる
+  assert_eq!(
+    game.elapse_time(2.22.into()),
+    vec![
+      PlayBgm("void.ogg".into()),
+      UpdateSentence(Sentence::empty())
+    ]
+  );
+  assert_eq!(
+    game.key_press("d".chars()),
+    vec![
+      Typed(MusicalTypeResult::Vacant),
+      UpdateSentence(Sentence::empty())
+    ]
+  );
+  assert_eq!(
+    game.elapse_time(0.5.into()),
+    vec![UpdateSentence(Sentence::new_with_inputted(
+      "打鍵テスト",
+      "だけんてすと",
+      "",
+    )?)]
+  );
+  assert_eq!(
+    game.elapse_time(1.0.into()),
+    vec![
+      UpdateSentence(Sentence::empty()),
+      MissedSentence(Sentence::new_with_inputted(
+        "打鍵テスト",
+        "だけんてすと",
+        "",
+      )?)
+    ]
+  );
+  assert_eq!(game.elapse_time(1.0.into()), vec![EndOfScore]);
+
+  Ok(())
+}
+
+#[test]
+fn section() -> Result<(), MusicalTyperError> {
+  let test_score = Scoremap::from_str(
+    r#"
+:song_data void.ogg
+
+[start]
+*1.0
+@Aメロ
+打鍵テスト
+:だけんてすと
+
+*2.0
+@サビ
+えっ
+:えっ
+
+*3.0
+[end]
+"#,
+    |config| config,
+  )?;
+
+  let mut game =
+    MusicalTyper::new(test_score, MusicalTyperConfig::default())?;
+  assert_eq!(game.section_name(), "");
+
+  use MusicalTyperEvent::*;
+  let entered: Vec<_> = [1.0, 1.0, 1.0]
+    .iter()
+    .flat_map(|time| game.elapse_time((*time).into()))
+    .filter(|event| matches!(event, EnterSection(_)))
+    .collect();
+  assert_eq!(
+    entered,
+    vec![EnterSection("Aメロ".into()), EnterSection("サビ".into())]
+  );
+  assert_eq!(game.section_name(), "サビ");
+
+  Ok(())
+}
+
+#[test]
+fn input_scheme() -> Result<(), MusicalTyperError> {
+  let test_score = Scoremap::from_str(
+    r#"
+:song_data void.ogg
+
+[start]
+*1.0
+簡単
+:かんたん
+
+*2.0
+[end]
+"#,
+    |config| config,
+  )?;
+
+  let mut game = MusicalTyper::new(
+    test_score,
+    MusicalTyperConfig::default().input_scheme(InputScheme::Azik),
+  )?;
+  let _ = game.elapse_time(1.0.into());
+  let events = game.key_press("kztz".chars());
+  assert!(events.iter().any(|event| matches!(
+    event,
+    MusicalTyperEvent::CompletedSentence(_)
+  )));
+
+  Ok(())
+}
+
+#[test]
+fn jis_kana_literals() -> Result<(), MusicalTyperError> {
+  let test_score = Scoremap::from_str(
+    r#"
+:song_data void.ogg
+
+[start]
+*1.0
+ゐ！A1
+:ゐ！A1
+
+*2.0
+[end]
+"#,
+    |config| config,
+  )?;
+
+  let mut game = MusicalTyper::new(
+    test_score,
+    MusicalTyperConfig::default().input_scheme(InputScheme::JisKana),
+  )?;
+  let _ = game.elapse_time(1.0.into());
+  // ゐ は い、記号と英数字はその刻印のキーの仮名で打つ
+  let events = game.key_press("いぬちぬ".chars());
+  assert!(events.iter().any(|event| matches!(
+    event,
+    MusicalTyperEvent::CompletedSentence(_)
+  )));
+
+  Ok(())
+}
+
+#[test]
+fn spelling_profile() -> Result<(), MusicalTyperError> {
+  let source = r#"
+:song_data void.ogg
+
+[start]
+*1.0
+父
+:ちち
+
+*2.0
+地
+:ち
+
+*3.0
+[end]
+"#;
+  let mut game = MusicalTyper::new(
+    Scoremap::from_str(source, |config| config)?,
+    MusicalTyperConfig::default(),
+  )?;
+  let _ = game.elapse_time(1.0.into());
+  assert_eq!(
+    game.activity().current_sentence().roman().will_input,
+    "titi"
+  );
+  let _ = game.key_press("chichi".chars());
+  assert_eq!(game.spelling_profile().rank("ち", "chi"), (false, 2));
+
+  // 覚えた打ち方が次の遊びで先に表示される
+  let mut game = MusicalTyper::new(
+    Scoremap::from_str(source, |config| config)?,
+    MusicalTyperConfig::default()
+      .spelling_profile(game.spelling_profile().clone()),
+  )?;
+  let _ = game.elapse_time(1.0.into());
+  assert_eq!(
+    game.activity().current_sentence().roman().will_input,
+    "chichi"
+  );
+
+  Ok(())
+}
+
+#[test]
+fn trailing_n() -> Result<(), MusicalTyperError> {
+  let source = r#"
+:song_data void.ogg
+
+[start]
+*1.0
+本
+:ほん
+
+*2.0
+[end]
+"#;
+  use MusicalTyperEvent::*;
+  for (trailing_n, completed) in [(false, false), (true, true)] {
+    let mut game = MusicalTyper::new(
+      Scoremap::from_str(source, |config| config)?,
+      MusicalTyperConfig::default().trailing_n(trailing_n),
+    )?;
+    let _ = game.elapse_time(1.0.into());
+    let events = game.key_press("hon".chars());
+    assert_eq!(
+      events
+        .iter()
+        .any(|event| matches!(event, CompletedSentence(_))),
+      completed
+    );
+  }
+  Ok(())
+}
+
+#[test]
+fn headless() -> Result<(), MusicalTyperError> {
+  use super::{run_headless, HeadlessLog, KeyEvent, StepClock};
+  let source = r#"
+:song_data void.ogg
+
+[start]
+*1.0
+打鍵
+:だけん
+
+*2.0
+早い
+:はやい
+
+*3.0
+[end]
+"#;
+  let keys: Vec<_> = [(1.05, "dakenn"), (2.05, "hayai")]
+    .iter()
+    .flat_map(|(start, typed)| {
+      typed.chars().enumerate().map(move |(index, key)| KeyEvent {
+        time: (start + index as f64 * 0.08).into(),
+        key,
+      })
+    })
+    .collect();
+
+  // 打鍵による出来事は、時計の刻み方によらず同じ時点に起きる
+  use MusicalTyperEvent::*;
+  let keyed = |log: &HeadlessLog| -> Vec<_> {
+    log
+      .events
+      .iter()
+      .filter(|(_, event)| {
+        matches!(event, Typed(_) | CompletedSentence(_))
+      })
+      .map(|(time, event)| (time.as_millis(), event.clone()))
+      .collect()
+  };
+  let mut logs = vec![];
+  for step in [0.001, 1.0 / 60.0, 0.5] {
+    logs.push(run_headless(
+      Scoremap::from_str(source, |config| config)?,
+      MusicalTyperConfig::default(),
+      &mut StepClock::new(step.into()),
+      keys.clone(),
+    )?);
+  }
+  for log in &logs {
+    assert_eq!(log.score.score_point, logs[0].score.score_point);
+    assert_eq!(keyed(log), keyed(&logs[0]));
+    assert!(matches!(log.events.last(), Some((_, EndOfScore))));
+  }
+  assert_eq!(
+    keyed(&logs[0])
+      .iter()
+      .filter(|(_, event)| matches!(event, CompletedSentence(_)))
+      .count(),
+    2
+  );
+  Ok(())
+}
+
+#[test]
+fn headless_stalled_clock() -> Result<(), MusicalTyperError> {
+  use super::{run_headless, KeyEvent, StepClock};
+  let source = r#"
+:song_data void.ogg
+
+[start]
+*1.0
+打鍵
+:だけん
+
+*2.0
+[end]
+"#;
+  // 進まない時計では譜面の終わりに届かないが、待ち続けずに返る
+  let log = run_headless(
+    Scoremap::from_str(source, |config| config)?,
+    MusicalTyperConfig::default(),
+    &mut StepClock::new(0.0.into()),
+    vec![KeyEvent {
+      time: 1.5.into(),
+      key: 'd',
+    }],
+  )?;
+  assert!(!log
+    .events
+    .iter()
+    .any(|(_, event)| *event == MusicalTyperEvent::EndOfScore));
+  Ok(())
+}
+
+#[test]
+fn replay() -> Result<(), MusicalTyperError> {
+  use super::{run_replay, score_hash, Replay};
+  let source = r#"
+:song_data void.ogg
+
+[start]
+*1.0
+打鍵
+:だけん
+
+*1.5
+速い
+:はやい
+
+*2.0
+遅い
+:おそい
+
+*2.5
+[end]
+"#;
+  let mut game = MusicalTyper::new(
+    Scoremap::from_str(source, |config| config)?,
+    MusicalTyperConfig::default(),
+  )?;
+  // 1 度に複数のノーツを飛ばす時間の進め方も、そのまま再現する
+  for (wait, typed) in [
+    (1.013, "dake"),
+    (0.017, "nxhay"),
+    (0.9, "ai"),
+    (0.021, "osoi"),
+    (3.0, ""),
+  ] {
+    let _ = game.elapse_time(wait.into());
+    let _ = game.key_press(typed.chars());
+  }
+  let recorded = game.activity().score().clone();
+
+  let replay =
+    Replay::parse(&game.replay(score_hash(source)).to_text())
+      .unwrap();
+  assert_eq!(replay.check_score(source), Ok(()));
+  let log = run_replay(
+    Scoremap::from_str(source, |config| config)?,
+    &replay,
+  )?;
+  assert!(log.score == recorded);
+  assert_eq!(log.score.score_point, recorded.score_point);
+  Ok(())
+}
+
+#[test]
+fn ghost() -> Result<(), MusicalTyperError> {
+  use super::{
+    best_replay, run_replay, score_hash, Clock, Ghost, Replay,
+    StepClock,
+  };
+  let source = r#"
+:song_data void.ogg
+
+[start]
+*1.0
+打鍵
+:だけん
+
+*2.0
+[end]
+"#;
+  let score = || Scoremap::from_str(source, |config| config);
+  let hash = score_hash(source);
+
+  // 1 文字ずつ打つ速さだけが違う 2 回の遊び
+  let play = |interval: f64| -> Result<_, MusicalTyperError> {
+    let mut game = MusicalTyper::new(score()?, Default::default())?;
+    let _ = game.elapse_time(1.0.into());
+    for typed in "daken".chars() {
+      let _ = game.elapse_time(interval.into());
+      let _ = game.key_press(Some(typed).into_iter());
+    }
+    let _ = game.elapse_time(2.0.into());
+    Ok(game.replay(hash))
+  };
+  let (slow, fast) = (play(0.15)?, play(0.1)?);
+  // 遊び終えたときの点数を残しておく
+  assert_eq!(
+    fast.score_point(),
+    Some(run_replay(score()?, &fast)?.score.score_point)
+  );
+  let best = best_replay(
+    &score()?,
+    hash,
+    &Default::default(),
+    vec![slow.clone(), fast.clone()],
+  )
+  .unwrap();
+  assert_eq!(best.to_text(), fast.to_text());
+  // 別のローマ字表で記録したものは、速くても選ばない
+  let other_table = Replay::parse(
+    &fast
+      .to_text()
+      .lines()
+      .map(|line| match line.starts_with("table\t") {
+        true => "table\t0000000000000000\n".to_owned(),
+        false => format!("{}\n", line),
+      })
+      .collect::<String>(),
+  )
+  .unwrap();
+  let best = best_replay(
+    &score()?,
+    hash,
+    &Default::default(),
+    vec![slow.clone(), other_table],
+  )
+  .unwrap();
+  assert_eq!(best.to_text(), slow.to_text());
+  // 残した点数で選び、選ぶために再生し直しはしない
+  let mut boasted = slow.clone();
+  boasted.set_score_point(i32::MAX);
+  let best = best_replay(
+    &score()?,
+    hash,
+    &Default::default(),
+    vec![fast.clone(), boasted],
+  )
+  .unwrap();
+  assert_eq!(best.score_point(), Some(i32::MAX));
+
+  // ゴーストと自分を 1 つの時計で進める
+  let mut ghost = Ghost::new(score()?, fast)?;
+  let mut player = MusicalTyper::new(score()?, slow.config())?;
+  let mut clock = StepClock::new(0.05.into());
+  let mut status = vec![];
+  for _ in 0..28 {
+    let now = clock.now();
+    let _ = player.feed(
+      &slow
+        .entries
+        .iter()
+        .filter(|entry| {
+          player.accumulated_time().as_millis()
+            < entry.time().as_millis()
+            && entry.time().as_millis() <= now.as_millis()
+        })
+        .cloned()
+        .collect::<Vec<_>>(),
+    );
+    ghost.catch_up(now);
+    status.push(ghost.status(&player));
+  }
+  // 1.3 秒の時点でゴーストは 3 文字、自分は 2 文字を打っている
+  assert_eq!(status[26].type_lead, -1);
+  assert_eq!(status[26].inputted_in_sentence, Some(3));
+  Ok(())
+}
+
+#[test]
+fn scoring() -> Result<(), MusicalTyperError> {
+  use super::{run_replay, Replay, ScoringProfile};
+  let source = r#"
+:song_data void.ogg
+
+[start]
+*1.0
+打鍵
+:だけん
+
+*2.0
+[end]
+"#;
+  let play =
+    |scoring: ScoringProfile| -> Result<_, MusicalTyperError> {
+      let mut game = MusicalTyper::new(
+        Scoremap::from_str(source, |config| config)?,
+        MusicalTyperConfig::default().scoring(scoring),
+      )?;
+      let _ = game.elapse_time(1.1.into());
+      let _ = game.key_press("dqakenn".chars());
+      let _ = game.elapse_time(1.0.into());
+      Ok(game)
+    };
+  let bundled = ScoringProfile::bundled();
+  // 正しく 6 打、誤って 1 打、文を打ち終える
+  assert_eq!(
+    play(bundled[0].clone())?.activity().score().score_point,
+    6 * 10 - 30 + 50
+  );
+  assert_eq!(
+    play(bundled[1].clone())?.activity().score().score_point,
+    6 * 10 - 5 + 50
+  );
+
+  // 付け方はリプレイに残り、再生しても同じ点数になる
+  let game = play(bundled[2].clone())?;
+  assert_eq!(game.activity().score().score_point, 50 + 6 * 10 - 60);
+  let replay = Replay::parse(&game.replay(0).to_text()).unwrap();
+  assert_eq!(replay.scoring(), game.scoring());
+  let log = run_replay(
+    Scoremap::from_str(source, |config| config)?,
+    &replay,
+  )?;
+  assert_eq!(
+    log.score.score_point,
+    game.activity().score().score_point
+  );
+  Ok(())
+}
+
+#[test]
+fn combo() -> Result<(), MusicalTyperError> {
+  use super::ScoringProfile;
+  use MusicalTyperEvent::*;
+  let scoring = ScoringProfile::parse_all(
+    "[test]\ncorrect_type\t10\nwrong_type\t30\ncombo\t3\t2\n",
+  )
+  .unwrap()
+  .remove(0);
+  let mut game = MusicalTyper::new(
+    Scoremap::from_str(
+      r#"
+:song_data void.ogg
+
+[start]
+*1.0
+打鍵
+:だけん
+
+*3.0
+[end]
+"#,
+      |config| config,
+    )?,
+    MusicalTyperConfig::default().scoring(scoring),
+  )?;
+  let _ = game.elapse_time(1.1.into());
+
+  let combo_events = |events: Vec<MusicalTyperEvent>| -> Vec<_> {
+    events
+      .into_iter()
+      .filter(|event| {
+        matches!(event, ComboMilestone(_) | ComboBroken(_))
+      })
+      .collect()
+  };
+  // 3 打目から倍率が上がり、打ち間違えると途切れる
+  assert_eq!(
+    combo_events(game.key_press("dak".chars())),
+    [ComboMilestone(3)]
+  );
+  assert_eq!(game.activity().score().score_point, 10 + 10 + 20);
+  assert_eq!(
+    combo_events(game.key_press("eq".chars())),
+    [ComboBroken(4)]
+  );
+  assert_eq!(game.activity().score().score_point, 40 + 20 - 30);
+  assert_eq!(game.activity().score().combo(), 0);
+  assert_eq!(game.activity().score().max_combo, 4);
+
+  let _ = game.key_press("nn".chars());
+  assert_eq!(game.activity().score().combo(), 2);
+  assert_eq!(game.activity().score().max_combo, 4);
+  Ok(())
+}
+
+#[test]
+fn life_gauge() -> Result<(), MusicalTyperError> {
+  use super::super::exp::game_activity::LifeRules;
+  use MusicalTyperEvent::*;
+  let source = r#"
+:song_data void.ogg
+
+[start]
+*1.0
+打鍵
+:だけん
+
+*2.0
+速い
+:はやい
+
+*3.0
+遅い
+:おそい
+
+*4.0
+[end]
+"#;
+  let rules = LifeRules {
+    max: 10.0,
+    wrong_type: 3.0,
+    missed_sentence: 5.0,
+    complete_sentence: 2.0,
+    perfect_sentence: 1.0,
+  };
+  let mut game = MusicalTyper::new(
+    Scoremap::from_str(source, |config| config)?,
+    MusicalTyperConfig::default().life_gauge(rules.clone()),
+  )?;
+  let life = |game: &MusicalTyper| {
+    game.activity().life_gauge().map(|gauge| gauge.life())
+  };
+
+  // 打ち間違えと打ち逃しで減り、打ち終えると戻る
+  let _ = game.elapse_time(1.1.into());
+  let _ = game.key_press("dqakenn".chars());
+  assert_eq!(life(&game), Some(10.0 - 3.0 + 2.0));
+  let _ = game.elapse_time(1.0.into());
+  let _ = game.key_press("ha".chars());
+  let events = game.elapse_time(1.0.into());
+  assert!(!events.contains(&Failed));
+  assert_eq!(life(&game), Some(9.0 - 5.0));
+
+  // 尽きたところで失敗し、以降は打っても進めても終わったまま
+  let events = game.key_press("qq".chars());
+  assert!(events.contains(&Failed));
+  assert!(game.activity().score().failed);
+  let progress = game.activity().score().progress;
+  assert!(0.0 < progress && progress < 1.0, "{}", progress);
+  assert_eq!(game.elapse_time(0.1.into()), [EndOfScore]);
+  assert!(game
+    .key_press("o".chars())
+    .contains(&Typed(MusicalTypeResult::Vacant)));
+  assert_eq!(game.activity().score().progress, progress);
+
+  // ライフゲージがなければ最後まで遊ぶ
+  let mut game = MusicalTyper::new(
+    Scoremap::from_str(source, |config| config)?,
+    MusicalTyperConfig::default(),
+  )?;
+  let _ = game.elapse_time(5.0.into());
+  assert_eq!(game.elapse_time(0.1.into()), [EndOfScore]);
+  assert!(!game.activity().score().failed);
+  assert_eq!(game.activity().score().progress, 1.0);
+  Ok(())
+}
+
+#[test]
+fn practice() -> Result<(), MusicalTyperError> {
+  use MusicalTyperEvent::*;
+  let mut game = MusicalTyper::new(
+    Scoremap::from_str(
+      r#"
+:song_data void.ogg
+
+[start]
+*0.5
+@intro
+
+*1.0
+打鍵
+:だけん
+
+*2.0
+速い
+:はやい
+
+*3.0
+@verse
+遅い
+:おそい
+
+*4.0
+[end]
+"#,
+      |config| config,
+    )?,
+    MusicalTyperConfig::default().practice(true),
+  )?;
+  let sentence =
+    |game: &MusicalTyper| game.activity().current_sentence();
+  let dakenn = |inputted| {
+    Sentence::new_with_inputted("打鍵", "だけん", inputted)
+  };
+  let _ = game.elapse_time(1.1.into());
+  let _ = game.key_press("dake".chars());
+  assert_eq!(sentence(&game), dakenn("dake")?);
+
+  // 止めている間は時間も打鍵も進まない
+  game.pause();
+  assert_eq!(game.elapse_time(5.0.into()), []);
+  assert_eq!(game.key_press("n".chars()), []);
+  assert_eq!(game.accumulated_time().as_millis(), 1100);
+  game.resume();
+
+  // 文の頭に戻ると打ち直しになる
+  let events = game.seek_note_by(0);
+  assert!(events.contains(&Seeked(1.0.into())));
+  assert_eq!(game.accumulated_time().as_millis(), 1000);
+  assert_eq!(sentence(&game), dakenn("")?);
+  let _ = game.key_press("dakenn".chars());
+  let _ = game.seek_note_by(1);
+  assert_eq!(
+    sentence(&game),
+    Sentence::new_with_inputted("速い", "はやい", "")?
+  );
+  let _ = game.seek_note_by(-1);
+  assert_eq!(sentence(&game), dakenn("")?);
+
+  // 名前の節に移り、その節を繰り返す
+  let events = game.seek_section("verse");
+  assert!(events.contains(&EnterSection("verse".into())));
+  assert_eq!(game.accumulated_time().as_millis(), 3000);
+  game.loop_current_section();
+  let _ = game.key_press("oso".chars());
+  let _ = game.elapse_time(0.5.into());
+  let events = game.elapse_time(1.6.into());
+  assert!(events.contains(&Seeked(3.0.into())));
+  assert_eq!(game.accumulated_time().as_millis(), 3000);
+  assert_eq!(
+    sentence(&game),
+    Sentence::new_with_inputted("遅い", "おそい", "")?
+  );
+  game.clear_loop();
+  assert_eq!(game.elapse_time(2.1.into()), [EndOfScore]);
+
+  // 練習はリプレイに残さない
+  assert!(game.replay(0).entries.is_empty());
+  Ok(())
+}
+
+#[test]
+fn speed() -> Result<(), MusicalTyperError> {
+  use super::{best_replay, score_hash};
+  let source = r#"
+:song_data void.ogg
+
+[start]
+*1.0
+打鍵
+:だけん
+
+*2.0
+[end]
+"#;
+  let score = || Scoremap::from_str(source, |config| config);
+  let hash = score_hash(source);
+  let config = MusicalTyperConfig::default().speed(0.5);
+  let mut game = MusicalTyper::new(score()?, config.clone())?;
+
+  // 半分の速さでは譜面の時間が倍になる
+  let starts: Vec<_> = game
+    .activity()
+    .note_starts()
+    .iter()
+    .map(|(_, start)| start.as_millis())
+    .collect();
+  assert_eq!(starts, [0, 2000, 4000]);
+  let _ = game.elapse_time(1.5.into());
+  assert_eq!(game.activity().current_sentence(), Sentence::empty());
+  let _ = game.elapse_time(0.6.into());
+  let _ = game.key_press("dake".chars());
+  assert_eq!(
+    game.activity().current_sentence(),
+    Sentence::new_with_inputted("打鍵", "だけん", "dake")?
+  );
+  assert_eq!(game.activity().score().speed, 0.5);
+
+  // 範囲の外は端に寄せる
+  assert_eq!(game.speed(), 0.5);
+  assert_eq!(
+    MusicalTyper::new(score()?, config.clone().speed(3.0))?.speed(),
+    1.5
+  );
+
+  // 速さの違う記録は比べない
+  let slow = game.replay(hash);
+  assert_eq!(slow.speed(), 0.5);
+  assert!(best_replay(
+    &score()?,
+    hash,
+    &Default::default(),
+    vec![slow.clone()]
+  )
+  .is_none());
+  assert!(best_replay(&score()?, hash, &config, vec![slow]).is_some());
+  Ok(())
+}
+
+#[test]
+fn note_logs() -> Result<(), MusicalTyperError> {
+  let mut game = MusicalTyper::new(
+    Scoremap::from_str(
+      r#"
+:song_data void.ogg
+
+[start]
+*1.0
+打鍵
+:だけん
+
+*2.0
+速い
+:はやい
+
+*3.0
+[end]
+"#,
+      |config| config,
+    )?,
+    Default::default(),
+  )?;
+  let _ = game.elapse_time(1.2.into());
+  let _ = game.key_press("dxake".chars());
+  let _ = game.elapse_time(0.3.into());
+  let _ = game.key_press("nn".chars());
+  let _ = game.elapse_time(0.6.into());
+  let _ = game.key_press("ha".chars());
+  let _ = game.elapse_time(1.0.into());
+
+  let logs = game.activity().note_logs();
+  assert_eq!(logs.len(), 2);
+  let (daken, hayai) = (&logs[0], &logs[1]);
+  assert_eq!(daken.position, (0, 1));
+  assert_eq!(daken.origin, "打鍵");
+  assert_eq!(daken.start.as_millis(), 1000);
+  assert_eq!(daken.first_type.map(|t| t.as_millis()), Some(200));
+  assert_eq!(daken.completed_at.map(|t| t.as_millis()), Some(500));
+  assert_eq!(daken.mistakes.get(&'a'), Some(&1));
+  assert_eq!(daken.keystrokes.len(), 7);
+  assert_eq!(daken.keystrokes[1].typed, 'x');
+  assert!(!daken.keystrokes[1].succeed);
+  assert_eq!(daken.achievement_rate, 1.0);
+  assert_eq!(daken.accuracy, 6.0 / 7.0);
+  assert!(!daken.missed);
+
+  // 打ち終えないまま終わった文
+  assert_eq!(hayai.first_type.map(|t| t.as_millis()), Some(100));
+  assert_eq!(hayai.completed_at, None);
+  assert!(hayai.mistakes.is_empty());
+  assert_eq!(hayai.achievement_rate, 2.0 / 5.0);
+  assert!(hayai.missed);
+  Ok(())
+}
+
+#[test]
+fn key_profile() -> Result<(), MusicalTyperError> {
+  use super::KeyProfile;
+  let play = |profile: KeyProfile| -> Result<_, MusicalTyperError> {
+    let mut game = MusicalTyper::new(
+      Scoremap::from_str(
+        r#"
+:song_data void.ogg
+
+[start]
+*1.0
+打鍵
+:だけん
+
+*2.0
+[end]
+"#,
+        |config| config,
+      )?,
+      MusicalTyperConfig::default().key_profile(profile),
+    )?;
+    let _ = game.elapse_time(1.2.into());
+    let _ = game.key_press("dsakenn".chars());
+    Ok(game.key_profile())
+  };
+
+  // 打つはずだったキーと打ったキー、その時の読み仮名を覚える
+  let first = play(KeyProfile::new())?;
+  assert_eq!(first.confusion('a', 's'), 1);
+  assert_eq!(first.kana("だ").map(|record| record.mistaken), Some(1));
+  assert_eq!(first.weakest_keys(1)[0].0, 'a');
+  assert_eq!(first.weakest_kana(1)[0].0, "だ");
+
+  // 前の遊びの分に足していく
+  let second = play(KeyProfile::parse(&first.to_tsv()))?;
+  assert_eq!(second.confusion('a', 's'), 2);
+  assert_eq!(second.key('n').map(|record| record.correct), Some(4));
+  Ok(())
+}
@@ -1,8 +1,12 @@
 use crate::model::exp::{
   game_activity::GameScore,
+  note::NoteLog,
   scoremap::{MusicInfo, Scoremap},
 };
-use crate::model::game::MusicalTyperError;
+use crate::model::game::{
+  MusicalTyperConfig, MusicalTyperError, Replay, ScoringProfile,
+};
+pub use game_view::data_dir;
 use game_view::GameView;
 use handler::{HandleError, Handler};
 use player::PlayerError;
@@ -17,6 +21,7 @@ mod player;
 mod renderer;
 mod result_view;
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum ViewError {
   ModelError(MusicalTyperError),
@@ -55,7 +60,16 @@ pub enum ViewRoute {
   SelectMusic,
   Start(Scoremap),
   Retry,
-  ResultView(GameScore, MusicInfo),
+  // 結果と、それを付けた点数の付け方と、文ごとの記録と、保存できなかったもの
+  ResultView(
+    GameScore,
+    MusicInfo,
+    ScoringProfile,
+    Vec<NoteLog>,
+    Vec<String>,
+  ),
+  // リプレイを再生する
+  Replay(Replay),
   Quit,
 }
 
@@ -68,27 +82,51 @@ impl From<PlayerError> for ViewError {
 struct Router<'ttf, 'canvas> {
   handler: Handler,
   renderer: RenderCtx<'ttf, 'canvas>,
+  // 遊ぶたびに使う設定
+  config: MusicalTyperConfig,
+  score_hash: u64,
+  with_ghost: bool,
 }
 
 impl<'ttf, 'canvas> Router<'ttf, 'canvas> {
   pub fn new(
     handler: Handler,
     renderer: Renderer<'ttf, 'canvas>,
+    config: MusicalTyperConfig,
+    score_hash: u64,
+    with_ghost: bool,
   ) -> Self {
     Self {
       handler,
       renderer: Rc::new(RefCell::new(renderer)),
+      config,
+      score_hash,
+      with_ghost,
     }
   }
 
-  pub fn run(self, score: Scoremap) -> Result<(), ViewError> {
-    let mut view: Option<Box<dyn View>> =
-      Some(Box::new(ResultView::new(
+  pub fn run(
+    self,
+    score: Scoremap,
+    replay: Option<Replay>,
+  ) -> Result<(), ViewError> {
+    let mut view: Option<Box<dyn View>> = match replay {
+      Some(replay) => Some(Box::new(GameView::replay(
+        self.renderer.clone(),
+        self.handler.clone(),
+        score.clone(),
+        replay,
+      )?)),
+      None => Some(Box::new(ResultView::new(
         self.renderer.clone(),
         self.handler.clone(),
         GameScore::new(0, 0.0, 0.0),
         score.metadata.get_music_info(),
-      )));
+        self.config.scoring_profile().clone(),
+        vec![],
+        vec![],
+      ))),
+    };
     while let Some(boxed_view) = view.as_mut() {
       let next = boxed_view.run()?;
       match next {
@@ -99,14 +137,34 @@ impl<'ttf, 'canvas> Router<'ttf, 'canvas> {
             self.renderer.clone(),
             self.handler.clone(),
             score.clone(),
+            self.score_hash,
+            self.config.clone(),
+            self.with_ghost,
+          )?));
+        }
+        ViewRoute::Replay(replay) => {
+          view.replace(Box::new(GameView::replay(
+            self.renderer.clone(),
+            self.handler.clone(),
+            score.clone(),
+            replay,
           )?));
         }
-        ViewRoute::ResultView(score, info) => {
+        ViewRoute::ResultView(
+          score,
+          info,
+          scoring,
+          note_logs,
+          save_errors,
+        ) => {
           view.replace(Box::new(ResultView::new(
             self.renderer.clone(),
             self.handler.clone(),
             score,
             info,
+            scoring,
+            note_logs,
+            save_errors,
           )));
         }
         ViewRoute::Quit => {
@@ -119,7 +177,13 @@ impl<'ttf, 'canvas> Router<'ttf, 'canvas> {
   }
 }
 
-pub fn run_router(score: Scoremap) -> Result<(), ViewError> {
+pub fn run_router(
+  score: Scoremap,
+  score_hash: u64,
+  config: MusicalTyperConfig,
+  replay: Option<Replay>,
+  with_ghost: bool,
+) -> Result<(), ViewError> {
   use std::path::Path;
 
   let sdl = sdl2::init().unwrap();
@@ -156,6 +220,7 @@ pub fn run_router(score: Scoremap) -> Result<(), ViewError> {
     Renderer::new(800, 600, canvas, font, &texture_creator)
       .expect("Fail to init a renderer");
 
-  Router::new(handler, renderer).run(score)?;
+  Router::new(handler, renderer, config, score_hash, with_ghost)
+    .run(score, replay)?;
   Ok(())
 }
@@ -1,7 +1,9 @@
 mod button;
 mod header;
+mod key_heatmap;
 mod stats;
 
 pub use button::*;
 pub use header::*;
+pub use key_heatmap::*;
 pub use stats::*;
@@ -41,8 +41,9 @@ impl Component for Header {
       score_point,
     } = &props;
 
-    let title = &music_info.title;
-    let author = &music_info.song_author;
+    let title = music_info.title.as_deref().unwrap_or("曲名不詳");
+    let author =
+      music_info.song_author.as_deref().unwrap_or("作曲者不詳");
 
     canvas.text(|s| {
       s.text(title)
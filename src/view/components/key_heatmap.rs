@@ -0,0 +1,92 @@
+use crate::{
+  model::game::KeyProfile,
+  view::renderer::{
+    text::TextAlign, Component, Renderer, ViewResult,
+  },
+};
+use sdl2::{pixels::Color, rect::Rect};
+
+const KEY_CHARS_ROWS: &[&str] = &[
+  "1234567890-^¥",
+  "qwertyuiop@[",
+  "asdfghjkl;:]",
+  "zxcvbnm,./\\",
+];
+const BACK: Color = Color::RGB(253, 243, 226);
+const HOT: Color = Color::RGB(250, 119, 109);
+const BLACK: Color = Color::RGB(0, 0, 0);
+const GRAY: Color = Color::RGB(195, 195, 190);
+
+// 打ち間違えの割合で BACK から HOT まで塗る
+fn heat_color(error_rate: f64) -> Color {
+  let mix = |from: u8, to: u8| {
+    (from as f64 + (to as f64 - from as f64) * error_rate) as u8
+  };
+  Color::RGB(
+    mix(BACK.r, HOT.r),
+    mix(BACK.g, HOT.g),
+    mix(BACK.b, HOT.b),
+  )
+}
+
+#[derive(PartialEq)]
+pub struct KeyHeatmapProps {
+  pub profile: KeyProfile,
+}
+
+// キーボードの並びで、キーごとの打ち間違えの多さを色で示す
+pub struct KeyHeatmap {
+  props: KeyHeatmapProps,
+  client: Rect,
+}
+
+impl KeyHeatmap {
+  pub fn new(props: KeyHeatmapProps, client: Rect) -> Self {
+    Self { props, client }
+  }
+}
+
+impl Component for KeyHeatmap {
+  type Props = KeyHeatmapProps;
+
+  fn is_needed_redraw(&self, new_props: &Self::Props) -> bool {
+    &self.props != new_props
+  }
+
+  fn update(&mut self, new_props: Self::Props) {
+    self.props = new_props;
+  }
+
+  fn render(&self, canvas: &mut Renderer<'_, '_>) -> ViewResult {
+    let cell_size =
+      self.client.height() / KEY_CHARS_ROWS.len() as u32;
+    for (y, row) in KEY_CHARS_ROWS.iter().enumerate() {
+      // 下の段ほど右にずらす
+      let indent = y as i32 * cell_size as i32 / 2;
+      for (x, key) in row.chars().enumerate() {
+        let cell = Rect::new(
+          self.client.x() + indent + x as i32 * cell_size as i32,
+          self.client.y() + y as i32 * cell_size as i32,
+          cell_size - 2,
+          cell_size - 2,
+        );
+        let record = self.props.profile.key(key);
+        canvas.set_draw_color(
+          record
+            .map_or(BACK, |record| heat_color(record.error_rate())),
+        );
+        canvas.fill_rect(cell)?;
+        canvas.set_draw_color(BLACK);
+        canvas.draw_rect(cell)?;
+        canvas.text(|s| {
+          s.text(&key.to_string().to_uppercase())
+            .color(if record.is_some() { BLACK } else { GRAY })
+            .align(TextAlign::Center)
+            .line_height(cell_size)
+            .pos(cell.center())
+        })?;
+      }
+    }
+    Ok(())
+  }
+}
@@ -5,15 +5,15 @@ use sdl2::{
 
 use super::super::renderer::{text::TextAlign, Renderer, ViewResult};
 use crate::{
-  model::exp::game_activity::GameScore, view::renderer::Component,
+  model::{exp::game_activity::GameScore, game::ScoringProfile},
+  view::renderer::Component,
 };
 
-mod rank;
-
 #[derive(PartialEq)]
 pub struct StatsProps {
   pub type_per_second: f64,
   pub score: GameScore,
+  pub scoring: ScoringProfile,
 }
 
 pub struct Stats {
@@ -43,6 +43,7 @@ impl Component for Stats {
     let &StatsProps {
       type_per_second,
       score,
+      scoring,
     } = &props;
 
     let accuracy = score.accuracy;
@@ -54,7 +55,7 @@ impl Component for Stats {
       Color::RGB(178, 255, 89)
     };
 
-    let rank = rank::rank(accuracy * 200.0);
+    let rank = scoring.rank(accuracy * 200.0);
 
     let speed_indicator_center =
       Point::new(client.width() as i32 / 2, client.y() + 15);
@@ -117,16 +118,32 @@ impl Component for Stats {
     })?;
 
     canvas.text(|s| {
-      s.text("ランク")
+      s.text(&format!("ランク ({})", scoring.name))
         .color(Color::RGB(160, 160, 165))
         .pos(client.top_left().offset(10, -40))
     })?;
     canvas.text(|s| {
-      s.text(rank.0)
+      s.text(rank)
         .color(Color::RGB(64, 79, 181))
         .line_height(25)
         .pos(client.top_left().offset(10, -25))
     })?;
+
+    let combo_pos = Point::new(
+      client.width() as i32 / 2 + client.x() + 10,
+      client.y(),
+    );
+    canvas.text(|s| {
+      s.text(&format!("コンボ (最大 {})", score.max_combo))
+        .color(Color::RGB(160, 160, 165))
+        .pos(combo_pos.offset(0, -40))
+    })?;
+    canvas.text(|s| {
+      s.text(&format!("{}", score.combo()))
+        .color(Color::RGB(250, 120, 110))
+        .line_height(25)
+        .pos(combo_pos.offset(0, -25))
+    })?;
     Ok(())
   }
 }
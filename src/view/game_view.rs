@@ -1,18 +1,25 @@
 use crate::model::{
-  exp::{scoremap::Scoremap, sentence::Sentence, time::Seconds},
+  exp::{
+    scoremap::Scoremap,
+    sentence::{roman::SpellingProfile, Sentence},
+    time::Seconds,
+  },
   game::{
-    MusicalTypeResult, MusicalTyper, MusicalTyperConfig,
-    MusicalTyperEvent,
+    best_replay, Clock, Ghost, KeyProfile, MusicalTypeResult,
+    MusicalTyper, MusicalTyperConfig, MusicalTyperEvent, Replay,
+    ReplayCursor, ReplayEntry, SystemClock,
   },
 };
 
-use sdl2::keyboard::Keycode;
+use sdl2::keyboard::{Keycode, Mod};
 
 use std::{
   collections::{BTreeSet, VecDeque},
+  path::PathBuf,
   time::Instant,
 };
 
+mod kana_layout;
 mod whole;
 
 use super::{
@@ -23,10 +30,41 @@ use super::{
 };
 use whole::{Whole, WholeProps};
 
+// 打ち方の癖を覚えておくファイル
+const SPELLING_PROFILE_PATH: &str = "spelling.tsv";
+// キーごとの打ち間違えと速さを溜めておくファイル
+const KEY_PROFILE_PATH: &str = "keys.tsv";
+// 遊ぶたびにリプレイを書き出す場所
+const REPLAY_DIR: &str = "replay";
+
+// 記録や手元の設定を置く場所。XDG_DATA_HOME か APPDATA、なければホームの .local/share の下
+pub fn data_dir() -> PathBuf {
+  data_dir_with(|name| {
+    std::env::var_os(name).filter(|v| !v.is_empty())
+  })
+}
+
+fn data_dir_with(
+  var: impl Fn(&str) -> Option<std::ffi::OsString>,
+) -> PathBuf {
+  let base = var("XDG_DATA_HOME")
+    .or_else(|| var("APPDATA"))
+    .map(PathBuf::from)
+    .or_else(|| {
+      var("HOME").map(|home| PathBuf::from(home).join(".local/share"))
+    })
+    .unwrap_or_default();
+  base.join("musical-typer")
+}
+
 pub struct GameView<'ttf, 'canvas> {
   renderer: RenderCtx<'ttf, 'canvas>,
   handler: Handler,
   model: MusicalTyper,
+  score_hash: u64,
+  // リプレイの再生中なら、その記録
+  playback: Option<ReplayCursor>,
+  ghost: Option<Ghost>,
 }
 
 impl<'ttf, 'canvas> GameView<'ttf, 'canvas> {
@@ -34,13 +72,128 @@ impl<'ttf, 'canvas> GameView<'ttf, 'canvas> {
     renderer: RenderCtx<'ttf, 'canvas>,
     handler: Handler,
     score: Scoremap,
+    score_hash: u64,
+    config: MusicalTyperConfig,
+    with_ghost: bool,
   ) -> Result<Self, ViewError> {
+    let spelling_profile =
+      std::fs::read_to_string(data_dir().join(SPELLING_PROFILE_PATH))
+        .map(|source| SpellingProfile::parse(&source))
+        .unwrap_or_default();
+    let key_profile =
+      std::fs::read_to_string(data_dir().join(KEY_PROFILE_PATH))
+        .map(|source| KeyProfile::parse(&source))
+        .unwrap_or_default();
+    let model = MusicalTyper::new(
+      score.clone(),
+      config
+        .clone()
+        .spelling_profile(spelling_profile)
+        .key_profile(key_profile),
+    )?;
+    // 練習では行ったり来たりするので、ゴーストとは並べない
+    let ghost = if with_ghost && !model.is_practice() {
+      best_replay(
+        &score,
+        score_hash,
+        &config,
+        saved_replays(model.speed()),
+      )
+      .map(|replay| Ghost::new(score.clone(), replay))
+      .transpose()?
+    } else {
+      None
+    };
     Ok(GameView {
       renderer,
       handler,
-      model: MusicalTyper::new(score, MusicalTyperConfig::default())?,
+      model,
+      score_hash,
+      playback: None,
+      ghost,
     })
   }
+
+  pub fn replay(
+    renderer: RenderCtx<'ttf, 'canvas>,
+    handler: Handler,
+    score: Scoremap,
+    replay: Replay,
+  ) -> Result<Self, ViewError> {
+    Ok(GameView {
+      renderer,
+      handler,
+      model: MusicalTyper::new(score, replay.config())?,
+      score_hash: replay.score_hash,
+      playback: Some(ReplayCursor::new(replay)),
+      ghost: None,
+    })
+  }
+
+  // 打ち方の癖とキーごとの記録、リプレイを書き出す。再生中は何も書かず、練習ではリプレイを書かない
+  // 書き出せなかったものを画面に出す文で返す
+  fn save(&self) -> Vec<String> {
+    if self.playback.is_some() {
+      return vec![];
+    }
+    let mut errors = vec![];
+    let mut write = |path: PathBuf, contents: String| {
+      let written = path
+        .parent()
+        .map_or(Ok(()), std::fs::create_dir_all)
+        .and_then(|_| std::fs::write(&path, contents));
+      if let Err(err) = written {
+        errors.push(format!(
+          "{} を保存できませんでした: {}",
+          path.display(),
+          err
+        ));
+      }
+    };
+    write(
+      data_dir().join(SPELLING_PROFILE_PATH),
+      self.model.spelling_profile().to_tsv(),
+    );
+    write(
+      data_dir().join(KEY_PROFILE_PATH),
+      self.model.key_profile().to_tsv(),
+    );
+    if !self.model.is_practice() {
+      let started = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map_or(0, |since| since.as_secs());
+      write(
+        replay_dir(self.model.speed())
+          .join(format!("{}.replay", started)),
+        self.model.replay(self.score_hash).to_text(),
+      );
+    }
+    errors
+  }
+}
+
+// 等速でない遊びは、等速のものと混ざらないよう速さごとの場所に分ける
+fn replay_dir(speed: f64) -> PathBuf {
+  let dir = data_dir().join(REPLAY_DIR);
+  if speed == 1.0 {
+    dir
+  } else {
+    dir.join(format!("speed{:.0}", speed * 100.0))
+  }
+}
+
+// これまでに speed の速さで保存したリプレイ全て。読めないものは飛ばす
+fn saved_replays(speed: f64) -> Vec<Replay> {
+  let entries = match std::fs::read_dir(replay_dir(speed)) {
+    Ok(entries) => entries,
+    Err(_) => return vec![],
+  };
+  entries
+    .filter_map(|entry| {
+      std::fs::read_to_string(entry.ok()?.path()).ok()
+    })
+    .filter_map(|source| Replay::parse(&source).ok())
+    .collect()
 }
 
 impl<'ttf, 'canvas> View for GameView<'ttf, 'canvas> {
@@ -52,8 +205,10 @@ impl<'ttf, 'canvas> View for GameView<'ttf, 'canvas> {
     let mut pressed_key_buf = BTreeSet::new();
     let mut typed_key_buf = vec![];
     let mut sentence = Sentence::empty();
+    let mut section_name = self.model.section_name().to_owned();
     let mut time_points = VecDeque::new();
     let mut ended = None;
+    let kana_input = self.model.input_scheme().is_kana();
 
     let client = sdl2::rect::Rect::new(
       0,
@@ -65,14 +220,26 @@ impl<'ttf, 'canvas> View for GameView<'ttf, 'canvas> {
       WholeProps {
         pressed_keys: pressed_key_buf.iter().cloned().collect(),
         sentence: sentence.clone(),
+        section_name: section_name.clone(),
+        kana_input,
         music_info: self.model.music_info(),
         type_per_second: 0.0,
         score: self.model.activity().score().clone(),
         section_remaining_ratio: self.model.section_remaining_ratio(),
+        ghost: None,
+        life: None,
+        practice: None,
+        scoring: self.model.scoring().clone(),
       },
       client,
     );
 
+    let playing_back = self.playback.is_some();
+    let practice = self.model.is_practice();
+    let mut practice_commands = vec![];
+    // 区間の繰り返しで、始まりだけ決めたところ
+    let mut loop_start = None;
+    let mut clock = SystemClock::new();
     loop {
       let time = Instant::now();
       {
@@ -80,7 +247,15 @@ impl<'ttf, 'canvas> View for GameView<'ttf, 'canvas> {
           use MusicalTyperEvent::*;
           match mt_event {
             PlayBgm(bgm_name) => {
-              player.change_bgm(bgm_name)?;
+              let position = self.model.accumulated_time();
+              let speed = self.model.speed();
+              reanchor_after(&mut clock, position, || {
+                player.change_bgm(bgm_name, speed)
+              })?;
+              player.seek_bgm(position)?;
+            }
+            EnterSection(name) => {
+              section_name = name.clone();
             }
             UpdateSentence(new_sentence) => {
               sentence = new_sentence.clone();
@@ -107,10 +282,26 @@ impl<'ttf, 'canvas> View for GameView<'ttf, 'canvas> {
               player.play_se(SEKind::PerfectSentence)?;
               // TODO: Queue a completed animation
             }
+            Failed => {
+              player.stop_bgm(500)?;
+              player.play_se(SEKind::GameOver)?;
+            }
             DidPerfectSection => {
               player.play_se(SEKind::PerfectSection)?;
               // TODO: Queue a perfect animation
             }
+            ComboMilestone(_combo) => {
+              player.play_se(SEKind::PerfectSentence)?;
+              // TODO: Queue a combo animation
+            }
+            ComboBroken(_combo) => {
+              // 打ち間違いの音は Typed で鳴らしている
+              // TODO: Queue a combo broken animation
+            }
+            Seeked(position) => {
+              player.seek_bgm(*position)?;
+              ended = None;
+            }
             EndOfScore => {
               if ended.is_none() {
                 ended =
@@ -129,25 +320,87 @@ impl<'ttf, 'canvas> View for GameView<'ttf, 'canvas> {
           }
           KeyDown {
             keycode: Some(keycode),
+            scancode: Some(scancode),
+            keymod,
             ..
           } => {
-            let key = keycode_to_char(keycode);
-            if pressed_key_buf.insert(key) {
-              typed_key_buf.push(key);
+            if playing_back {
+              return;
+            }
+            if practice {
+              if let Some(command) =
+                PracticeCommand::from_key(keycode)
+              {
+                practice_commands.push(command);
+                return;
+              }
+            }
+            let shift =
+              keymod.intersects(Mod::LSHIFTMOD | Mod::RSHIFTMOD);
+            // かな入力では押したキーの刻印と打った仮名が異なる
+            // 修飾キーだけを押したときは何も打っていない
+            if kana_input {
+              let key = match kana_layout::scancode_to_key(scancode) {
+                Some(key) => key,
+                None => return,
+              };
+              if pressed_key_buf.insert(key) {
+                typed_key_buf
+                  .extend(kana_layout::key_to_kana(key, shift));
+              }
+              return;
+            }
+            if let Some(key) = keycode_to_char(keycode, shift) {
+              if pressed_key_buf.insert(key) {
+                typed_key_buf.push(key);
+              }
             }
           }
           KeyUp {
             keycode: Some(keycode),
+            scancode: Some(scancode),
             ..
           } => {
-            pressed_key_buf.remove(&keycode_to_char(keycode));
+            if playing_back {
+              return;
+            }
+            if kana_input {
+              if let Some(key) =
+                kana_layout::scancode_to_key(scancode)
+              {
+                pressed_key_buf.remove(&key);
+              }
+              return;
+            }
+            for shift in [false, true] {
+              if let Some(key) = keycode_to_char(keycode, shift) {
+                pressed_key_buf.remove(&key);
+              }
+            }
           }
           _ => {}
         })?;
         if should_quit {
           player.stop_bgm(500)?;
           player.play_se(SEKind::GameOver)?;
-          self.handler.delay(2500)?;
+          // 閉じる前に、書き出せなかったことを見せておく
+          let errors = self.save();
+          for (index, error) in errors.iter().enumerate() {
+            self.renderer.borrow_mut().text(|style| {
+              style
+                .text(error)
+                .color(sdl2::pixels::Color::RGB(250, 119, 109))
+                .line_height(20)
+                .pos(sdl2::rect::Point::new(
+                  20,
+                  10 + index as i32 * 25,
+                ))
+            })?;
+          }
+          if !errors.is_empty() {
+            self.renderer.borrow_mut().flush();
+            self.handler.delay(2500)?;
+          }
           return Ok(ViewRoute::Quit);
         }
       }
@@ -167,10 +420,24 @@ impl<'ttf, 'canvas> View for GameView<'ttf, 'canvas> {
       whole_view.update(WholeProps {
         pressed_keys: pressed_key_buf.iter().cloned().collect(),
         sentence: sentence.clone(),
+        section_name: section_name.clone(),
+        kana_input,
         music_info: self.model.music_info(),
         type_per_second,
         score: self.model.activity().score().clone(),
         section_remaining_ratio: self.model.section_remaining_ratio(),
+        ghost: self
+          .ghost
+          .as_ref()
+          .map(|ghost| ghost.status(&self.model)),
+        life: self
+          .model
+          .activity()
+          .life_gauge()
+          .map(|gauge| gauge.ratio()),
+        practice: practice
+          .then(|| practice_status(&self.model, loop_start)),
+        scoring: self.model.scoring().clone(),
       });
       whole_view.render(&mut self.renderer.borrow_mut())?;
 
@@ -178,8 +445,48 @@ impl<'ttf, 'canvas> View for GameView<'ttf, 'canvas> {
 
       let typed_key_buf_cloned = typed_key_buf.clone();
       typed_key_buf.clear();
-      mt_events =
-        self.model.key_press(typed_key_buf_cloned.into_iter());
+      if !playing_back {
+        mt_events =
+          self.model.key_press(typed_key_buf_cloned.into_iter());
+      }
+      for command in practice_commands.drain(..) {
+        use PracticeCommand::*;
+        match command {
+          TogglePause if self.model.is_paused() => {
+            self.model.resume();
+            player.resume_bgm();
+          }
+          TogglePause => {
+            self.model.pause();
+            player.pause_bgm();
+          }
+          SeekNote(count) => {
+            mt_events.append(&mut self.model.seek_note_by(count))
+          }
+          SeekSection(count) => {
+            mt_events.append(&mut self.model.seek_section_by(count))
+          }
+          LoopSection if self.model.loop_range().is_some() => {
+            self.model.clear_loop();
+          }
+          LoopSection => self.model.loop_current_section(),
+          MarkLoop => {
+            let note = self.model.activity().current_note();
+            let duration = note.map(|note| note.duration().clone());
+            match (loop_start.take(), duration) {
+              (None, Some(duration)) => {
+                loop_start = Some(duration.start())
+              }
+              (Some(start), Some(duration)) => {
+                self.model.set_loop(start, duration.end())
+              }
+              _ => {}
+            }
+          }
+        }
+        // 止めていた間や移った先から時計を進め直す
+        clock.set(self.model.accumulated_time());
+      }
 
       let draw_time = time.elapsed().as_secs_f64();
 
@@ -187,31 +494,161 @@ impl<'ttf, 'canvas> View for GameView<'ttf, 'canvas> {
         .handler
         .delay((1e3 / 60.0 - draw_time * 1e3).max(0.0) as u32)?;
 
-      let elapsed = time.elapsed().as_secs_f64();
-
-      mt_events.append(&mut self.model.elapse_time(elapsed.into()));
-      print!(
-        "\rFPS: {}, Playing: {}     ",
-        1.0 / draw_time,
-        sdl2::mixer::Music::is_playing()
-      );
-
-      if ended
-        .as_ref()
-        .map_or(false, |ended| ended < &self.model.accumulated_time())
+      let now = clock.now();
+      if let Some(ghost) = self.ghost.as_mut() {
+        ghost.catch_up(now);
+      }
+      if let Some(cursor) = self.playback.as_mut() {
+        // 記録した時点まで進め、打ったキーはそのフレームの間だけ押して見せる
+        let entries = cursor.until(now);
+        pressed_key_buf = entries
+          .iter()
+          .filter_map(|entry| match entry {
+            ReplayEntry::Key(_, key) if kana_input => {
+              kana_layout::kana_to_key(*key)
+            }
+            ReplayEntry::Key(_, key) => Some(*key),
+            _ => None,
+          })
+          .collect();
+        mt_events = self.model.feed(entries);
+      } else {
+        let elapsed = now - self.model.accumulated_time();
+        let mut events = self.model.elapse_time(elapsed);
+        // 繰り返しで区間の始まりに戻った
+        if events
+          .iter()
+          .any(|event| matches!(event, MusicalTyperEvent::Seeked(_)))
+        {
+          clock.set(self.model.accumulated_time());
+        }
+        mt_events.append(&mut events);
+      }
+      let replay_finished =
+        self.playback.as_ref().is_some_and(ReplayCursor::finished);
+      if replay_finished
+        || ended
+          .as_ref()
+          .is_some_and(|ended| ended < &self.model.accumulated_time())
       {
+        let save_errors = self.save();
         return Ok(ViewRoute::ResultView(
           self.model.activity().score().clone(),
           self.model.music_info(),
+          self.model.scoring().clone(),
+          self.model.activity().note_logs(),
+          save_errors,
         ));
       }
     }
   }
 }
 
-fn keycode_to_char(keycode: Keycode) -> char {
+// 練習中に使うキーでの操作
+enum PracticeCommand {
+  TogglePause,
+  SeekNote(isize),
+  SeekSection(isize),
+  // 今の節を繰り返す。繰り返していればやめる
+  LoopSection,
+  // 1 度目で今の文を区間の始まりに、2 度目で今の文までを区間にして繰り返す
+  MarkLoop,
+}
+
+impl PracticeCommand {
+  fn from_key(keycode: Keycode) -> Option<Self> {
+    use PracticeCommand::*;
+    Some(match keycode {
+      Keycode::F1 => TogglePause,
+      Keycode::Left => SeekNote(-1),
+      Keycode::Right => SeekNote(1),
+      Keycode::Up => SeekSection(-1),
+      Keycode::Down => SeekSection(1),
+      Keycode::F2 => LoopSection,
+      Keycode::F3 => MarkLoop,
+      _ => return None,
+    })
+  }
+}
+
+fn practice_status(
+  model: &MusicalTyper,
+  loop_start: Option<Seconds>,
+) -> String {
+  let mut state = if model.is_paused() {
+    "一時停止中".to_owned()
+  } else {
+    "練習中".to_owned()
+  };
+  if model.speed() != 1.0 {
+    state.push_str(&format!(" (速度 {:.0}%)", model.speed() * 100.0));
+  }
+  let looping = match (model.loop_range(), loop_start) {
+    (Some((from, to)), _) => {
+      format!(
+        "{:.1}s から {:.1}s を繰り返し",
+        from.as_f64(),
+        to.as_f64()
+      )
+    }
+    (None, Some(from)) => {
+      format!(
+        "{:.1}s から繰り返す区間の終わりを F3 で決める",
+        from.as_f64()
+      )
+    }
+    (None, None) => {
+      "F1 停止 ←→ 文 ↑↓ 節 F2 節を繰り返す F3 区間".into()
+    }
+  };
+  format!("{} / {}", state, looping)
+}
+
+// 曲の読み込みを終えてから、時計を position から進め直す
+// 等速でない曲は伸び縮みさせるのに時間がかかり、その間に譜面だけが先へ進んでしまう
+fn reanchor_after<T>(
+  clock: &mut SystemClock,
+  position: Seconds,
+  load: impl FnOnce() -> T,
+) -> T {
+  let loaded = load();
+  clock.set(position);
+  loaded
+}
+
+// 文字を打たないキー (Shift などの修飾キー) は None
+// 鍵盤の表示 (KEY_CHARS_ROWS) に合わせて JIS 配列とみなす
+fn keycode_to_char(keycode: Keycode, shift: bool) -> Option<char> {
   use Keycode::*;
-  match keycode {
+  if shift {
+    let c = match keycode_to_char(keycode, false)? {
+      c if c.is_ascii_alphabetic() => c.to_ascii_uppercase(),
+      '1' => '!',
+      '2' => '"',
+      '3' => '#',
+      '4' => '$',
+      '5' => '%',
+      '6' => '&',
+      '7' => '\'',
+      '8' => '(',
+      '9' => ')',
+      '-' => '=',
+      '^' => '~',
+      '@' => '`',
+      '[' => '{',
+      ';' => '+',
+      ':' => '*',
+      ']' => '}',
+      ',' => '<',
+      '.' => '>',
+      '/' => '?',
+      '\\' => '_',
+      ' ' => ' ',
+      _ => return None,
+    };
+    return Some(c);
+  }
+  let c = match keycode {
     A => 'a',
     B => 'b',
     C => 'c',
@@ -238,7 +675,98 @@ fn keycode_to_char(keycode: Keycode) -> char {
     X => 'x',
     Y => 'y',
     Z => 'z',
+    Num0 => '0',
+    Num1 => '1',
+    Num2 => '2',
+    Num3 => '3',
+    Num4 => '4',
+    Num5 => '5',
+    Num6 => '6',
+    Num7 => '7',
+    Num8 => '8',
+    Num9 => '9',
     Minus => '-',
-    _ => '\0',
-  }
+    Caret => '^',
+    At => '@',
+    LeftBracket => '[',
+    Semicolon => ';',
+    Colon => ':',
+    RightBracket => ']',
+    Comma => ',',
+    Period => '.',
+    Slash => '/',
+    Backslash => '\\',
+    Space => ' ',
+    _ => return None,
+  };
+  Some(c)
+}
+
+#[test]
+fn modifier_keys_type_nothing() {
+  use Keycode::*;
+  assert_eq!(keycode_to_char(LShift, true), None);
+  assert_eq!(keycode_to_char(RShift, false), None);
+  assert_eq!(keycode_to_char(A, true), Some('A'));
+  // US 配列にしかないキーは打てない
+  assert_eq!(keycode_to_char(Quote, false), None);
+}
+
+#[test]
+fn shifted_keys_follow_jis() {
+  use Keycode::*;
+  let row = [
+    Num1, Num2, Num3, Num4, Num5, Num6, Num7, Num8, Num9, Minus,
+    Caret,
+  ];
+  let shifted: String = row
+    .iter()
+    .filter_map(|&keycode| keycode_to_char(keycode, true))
+    .collect();
+  assert_eq!(shifted, "!\"#$%&'()=~");
+  assert_eq!(keycode_to_char(Num0, true), None);
+  assert_eq!(keycode_to_char(At, true), Some('`'));
+  assert_eq!(keycode_to_char(Colon, true), Some('*'));
+  assert_eq!(keycode_to_char(Semicolon, true), Some('+'));
+  assert_eq!(keycode_to_char(Backslash, true), Some('_'));
+}
+
+#[test]
+fn clock_waits_for_slow_bgm() {
+  let mut clock = SystemClock::new();
+  let position: Seconds = 0.5.into();
+  reanchor_after(&mut clock, position, || {
+    std::thread::sleep(std::time::Duration::from_millis(300))
+  });
+  // 読み込みにかかった 0.3 秒は進んでいない
+  let now = clock.now();
+  assert!(
+    position.as_millis() <= now.as_millis()
+      && now.as_millis() < position.as_millis() + 200,
+    "{:?}",
+    now
+  );
+}
+
+#[test]
+fn data_dir_under_user_data() {
+  let vars = |pairs: &'static [(&str, &str)]| {
+    move |name: &str| {
+      pairs
+        .iter()
+        .find(|(key, _)| *key == name)
+        .map(|(_, value)| value.into())
+    }
+  };
+  assert_eq!(
+    data_dir_with(vars(&[
+      ("XDG_DATA_HOME", "/data"),
+      ("HOME", "/home/a")
+    ])),
+    PathBuf::from("/data/musical-typer")
+  );
+  assert_eq!(
+    data_dir_with(vars(&[("HOME", "/home/a")])),
+    PathBuf::from("/home/a/.local/share/musical-typer")
+  );
 }
@@ -0,0 +1,130 @@
+use sdl2::keyboard::Scancode;
+
+// JIS かな配列のキーの刻印と、そのキーで打てる仮名
+const KANA_KEYS: &[(char, char, Option<char>)] = &[
+  ('1', 'ぬ', None),
+  ('2', 'ふ', None),
+  ('3', 'あ', Some('ぁ')),
+  ('4', 'う', Some('ぅ')),
+  ('5', 'え', Some('ぇ')),
+  ('6', 'お', Some('ぉ')),
+  ('7', 'や', Some('ゃ')),
+  ('8', 'ゆ', Some('ゅ')),
+  ('9', 'よ', Some('ょ')),
+  ('0', 'わ', Some('を')),
+  ('-', 'ほ', None),
+  ('^', 'へ', None),
+  ('¥', 'ー', None),
+  ('q', 'た', None),
+  ('w', 'て', None),
+  ('e', 'い', Some('ぃ')),
+  ('r', 'す', None),
+  ('t', 'か', None),
+  ('y', 'ん', None),
+  ('u', 'な', None),
+  ('i', 'に', None),
+  ('o', 'ら', None),
+  ('p', 'せ', None),
+  ('@', '゛', None),
+  ('[', '゜', Some('「')),
+  ('a', 'ち', None),
+  ('s', 'と', None),
+  ('d', 'し', None),
+  ('f', 'は', None),
+  ('g', 'き', None),
+  ('h', 'く', None),
+  ('j', 'ま', None),
+  ('k', 'の', None),
+  ('l', 'り', None),
+  (';', 'れ', None),
+  (':', 'け', None),
+  (']', 'む', Some('」')),
+  ('z', 'つ', Some('っ')),
+  ('x', 'さ', None),
+  ('c', 'そ', None),
+  ('v', 'ひ', None),
+  ('b', 'こ', None),
+  ('n', 'み', None),
+  ('m', 'も', None),
+  (',', 'ね', Some('、')),
+  ('.', 'る', Some('。')),
+  ('/', 'め', Some('・')),
+  ('\\', 'ろ', None),
+];
+
+// キーの位置から JIS 配列の刻印を求める
+// 仮名を打たないキーは None
+pub fn scancode_to_key(scancode: Scancode) -> Option<char> {
+  use Scancode::*;
+  let key = match scancode {
+    Num1 => '1',
+    Num2 => '2',
+    Num3 => '3',
+    Num4 => '4',
+    Num5 => '5',
+    Num6 => '6',
+    Num7 => '7',
+    Num8 => '8',
+    Num9 => '9',
+    Num0 => '0',
+    Minus => '-',
+    Equals => '^',
+    International3 => '¥',
+    Q => 'q',
+    W => 'w',
+    E => 'e',
+    R => 'r',
+    T => 't',
+    Y => 'y',
+    U => 'u',
+    I => 'i',
+    O => 'o',
+    P => 'p',
+    LeftBracket => '@',
+    RightBracket => '[',
+    A => 'a',
+    S => 's',
+    D => 'd',
+    F => 'f',
+    G => 'g',
+    H => 'h',
+    J => 'j',
+    K => 'k',
+    L => 'l',
+    Semicolon => ';',
+    Apostrophe => ':',
+    Backslash | NonUsHash => ']',
+    Z => 'z',
+    X => 'x',
+    C => 'c',
+    V => 'v',
+    B => 'b',
+    N => 'n',
+    M => 'm',
+    Comma => ',',
+    Period => '.',
+    Slash => '/',
+    International1 => '\\',
+    _ => return None,
+  };
+  Some(key)
+}
+
+// 小書きのないキーは Shift を押していても同じ仮名
+pub fn key_to_kana(key: char, shift: bool) -> Option<char> {
+  let found = KANA_KEYS.iter().find(|(label, ..)| *label == key);
+  match found {
+    Some((_, _, Some(shifted))) if shift => Some(*shifted),
+    Some((_, kana, _)) => Some(*kana),
+    _ => None,
+  }
+}
+
+pub fn kana_to_key(kana: char) -> Option<char> {
+  KANA_KEYS
+    .iter()
+    .find(|(_, unshifted, shifted)| {
+      *unshifted == kana || *shifted == Some(kana)
+    })
+    .map(|(label, ..)| *label)
+}
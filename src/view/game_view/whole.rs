@@ -1,8 +1,12 @@
 use sdl2::{pixels::Color, rect::Rect};
 
 use crate::{
-  model::exp::{
-    game_activity::GameScore, scoremap::MusicInfo, sentence::Sentence,
+  model::{
+    exp::{
+      game_activity::GameScore, scoremap::MusicInfo,
+      sentence::Sentence,
+    },
+    game::{GhostStatus, ScoringProfile},
   },
   view::{
     components::{Header, HeaderProps, Stats, StatsProps},
@@ -13,6 +17,7 @@ use crate::{
 mod finder;
 mod keyboard;
 
+use super::kana_layout::kana_to_key;
 use finder::{Finder, FinderProps};
 use keyboard::{Keyboard, KeyboardProps};
 
@@ -20,10 +25,28 @@ use keyboard::{Keyboard, KeyboardProps};
 pub struct WholeProps {
   pub pressed_keys: Vec<char>,
   pub sentence: Sentence,
+  pub section_name: String,
+  pub kana_input: bool,
   pub music_info: MusicInfo,
   pub type_per_second: f64,
   pub score: GameScore,
   pub section_remaining_ratio: f64,
+  pub ghost: Option<GhostStatus>,
+  // ライフゲージを使っていれば、残りの割合
+  pub life: Option<f64>,
+  // 練習中なら、その様子
+  pub practice: Option<String>,
+  pub scoring: ScoringProfile,
+}
+
+// 次に打てるキー
+fn hint(props: &WholeProps) -> Vec<char> {
+  let next = props.sentence.next_keys();
+  if props.kana_input {
+    next.into_iter().filter_map(kana_to_key).collect()
+  } else {
+    next
+  }
 }
 
 pub struct Whole {
@@ -31,22 +54,20 @@ pub struct Whole {
   finder: Finder,
   header: Header,
   stats: Stats,
+  life: Option<f64>,
+  practice: Option<String>,
   client: Rect,
 }
 
 impl Whole {
   pub fn new(props: WholeProps, client: Rect) -> Self {
-    let hint = {
-      let roman = props.sentence.roman();
-      roman.will_input.chars().next().map_or(vec![], |c| vec![c])
-    };
     let keyboard_dim =
       Rect::new(0, client.height() as i32 - 350, client.width(), 200);
 
     let keyboard = Keyboard::new(
       KeyboardProps {
         pressed_keys: props.pressed_keys.clone(),
-        highlighted_keys: hint,
+        highlighted_keys: hint(&props),
       },
       keyboard_dim,
     );
@@ -55,7 +76,9 @@ impl Whole {
     let finder = Finder::new(
       FinderProps {
         sentence: props.sentence.clone(),
+        section_name: props.section_name.clone(),
         remaining_ratio: props.section_remaining_ratio,
+        ghost: props.ghost.clone(),
       },
       finder_dim,
     );
@@ -75,6 +98,7 @@ impl Whole {
       StatsProps {
         type_per_second: props.type_per_second,
         score: props.score,
+        scoring: props.scoring.clone(),
       },
       stats_dim,
     );
@@ -84,6 +108,8 @@ impl Whole {
       finder,
       header,
       stats,
+      life: props.life,
+      practice: props.practice.clone(),
       client,
     }
   }
@@ -97,24 +123,25 @@ impl Component for Whole {
   }
 
   fn update(&mut self, props: Self::Props) {
-    let hint = {
-      let roman = props.sentence.roman();
-      roman.will_input.chars().next().map_or(vec![], |c| vec![c])
-    };
-
     self.keyboard.update(KeyboardProps {
       pressed_keys: props.pressed_keys.clone(),
-      highlighted_keys: hint,
+      highlighted_keys: hint(&props),
     });
 
     self.finder.update(FinderProps {
       sentence: props.sentence.clone(),
+      section_name: props.section_name.clone(),
       remaining_ratio: props.section_remaining_ratio,
+      ghost: props.ghost.clone(),
     });
 
+    self.life = props.life;
+    self.practice = props.practice.clone();
+
     self.stats.update(StatsProps {
       type_per_second: props.type_per_second,
       score: props.score,
+      scoring: props.scoring,
     });
   }
 
@@ -131,6 +158,30 @@ impl Component for Whole {
       ctx.draw_rect(header_dim)?;
     }
 
+    if let Some(life) = self.life {
+      let color = if 0.25 < life {
+        Color::RGB(178, 255, 89)
+      } else {
+        Color::RGB(250, 119, 109)
+      };
+      ctx.set_draw_color(color);
+      ctx.fill_rect(Rect::new(
+        0,
+        100,
+        (client.width() as f64 * life) as u32,
+        6,
+      ))?;
+    }
+
+    if let Some(practice) = &self.practice {
+      ctx.text(|s| {
+        s.text(practice)
+          .color(Color::RGB(64, 79, 181))
+          .line_height(20)
+          .pos(client.top_left().offset(10, 108))
+      })?;
+    }
+
     self.finder.render(ctx)?;
 
     {
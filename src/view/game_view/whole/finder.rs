@@ -4,7 +4,10 @@ use sdl2::{
 };
 
 use crate::{
-  model::exp::sentence::{Sentence, TypingStr},
+  model::{
+    exp::sentence::{Sentence, TypingStr},
+    game::GhostStatus,
+  },
   view::renderer::{
     text::TextAlign, Component, Renderer, ViewResult,
   },
@@ -13,7 +16,9 @@ use crate::{
 #[derive(PartialEq)]
 pub struct FinderProps {
   pub sentence: Sentence,
+  pub section_name: String,
   pub remaining_ratio: f64,
+  pub ghost: Option<GhostStatus>,
 }
 
 pub struct Finder {
@@ -22,6 +27,7 @@ pub struct Finder {
 }
 
 impl Finder {
+  #[allow(clippy::manual_clamp)]
   pub fn new(mut initial_props: FinderProps, client: Rect) -> Self {
     initial_props.remaining_ratio =
       initial_props.remaining_ratio.max(0.).min(1.);
@@ -48,6 +54,8 @@ impl Component for Finder {
     let &FinderProps {
       remaining_ratio,
       sentence,
+      section_name,
+      ghost,
     } = &props;
 
     canvas.set_draw_color(Color::RGB(230, 220, 200));
@@ -74,6 +82,14 @@ impl Component for Finder {
         .pos(client.top_left())
     })?;
 
+    canvas.text(|s| {
+      s.color(Color::RGB(120, 110, 100))
+        .text(section_name)
+        .line_height(JAPANESE_HEIGHT)
+        .align(TextAlign::Right)
+        .pos(client.top_right())
+    })?;
+
     const ROMAN_HEIGHT: u32 = 40;
     {
       let TypingStr {
@@ -104,6 +120,32 @@ impl Component for Finder {
             client.bottom() - ROMAN_HEIGHT as i32 - 20,
           ))
       })?;
+
+      // 同じ文を打っているゴーストの位置。等幅なので文字数から求める
+      if let Some(ghost_inputted) =
+        ghost.as_ref().and_then(|ghost| ghost.inputted_in_sentence)
+      {
+        let ahead = ghost_inputted as i32 - inputted.len() as i32;
+        canvas.set_draw_color(Color::RGB(120, 160, 220));
+        canvas.fill_rect(Rect::new(
+          half_x as i32 + ahead * ROMAN_HEIGHT as i32 / 2 - 1,
+          client.bottom() - 20,
+          3,
+          10,
+        ))?;
+      }
+    }
+    if let Some(ghost) = ghost {
+      canvas.text(|s| {
+        s.color(Color::RGB(120, 160, 220))
+          .text(&format!(
+            "ゴースト {:+} 打 {:+} 点",
+            ghost.type_lead, ghost.point_lead
+          ))
+          .line_height(20)
+          .align(TextAlign::Right)
+          .pos(client.bottom_right().offset(-5, -20))
+      })?;
     }
     const YOMIGANA_HEIGHT: u32 = 80;
     {
@@ -1,7 +1,11 @@
+use crate::model::exp::time::Seconds;
 use sdl2::mixer::{Channel, Chunk, Music};
 use std::{collections::HashMap, path::Path};
+use time_stretch::time_stretch;
 use PlayerError::*;
 
+mod time_stretch;
+
 pub enum SEKind {
   Correct,
   Fail,
@@ -26,17 +30,86 @@ impl From<std::io::Error> for PlayerError {
 
 type Chunks = HashMap<String, Chunk>;
 
+// 速さを変えた曲だけを鳴らすチャンネル。効果音には使わない
+const BGM_CHANNEL: Channel = Channel(0);
+
+// 音の高さを変えずに速さを変えた曲
+struct StretchedBgm {
+  // samples を指しているので、samples より先に解放する
+  chunk: Chunk,
+  samples: Vec<i16>,
+  frequency: i32,
+  channels: usize,
+}
+
+impl StretchedBgm {
+  fn load(path: &Path, speed: f64) -> Result<Self, PlayerError> {
+    let (frequency, format, channels) =
+      sdl2::mixer::query_spec().map_err(AudioError)?;
+    if format != sdl2::mixer::AUDIO_S16SYS {
+      return Err(AudioError(
+        "changing speed needs 16 bit audio".into(),
+      ));
+    }
+    let decoded = Chunk::from_file(path).map_err(AudioError)?;
+    // Chunk::from_file は開いたときの形式に変換した音を abuf に alen バイト置く
+    let samples = unsafe {
+      std::slice::from_raw_parts(
+        (*decoded.raw).abuf as *const i16,
+        (*decoded.raw).alen as usize / 2,
+      )
+    };
+    let samples = time_stretch(samples, channels as usize, speed);
+    // QuickLoad_RAW は samples を写さずに指すだけで、解放もしない
+    let raw = unsafe {
+      sdl2::sys::mixer::Mix_QuickLoad_RAW(
+        samples.as_ptr() as *mut u8,
+        (samples.len() * 2) as u32,
+      )
+    };
+    if raw.is_null() {
+      return Err(AudioError(sdl2::get_error()));
+    }
+    Ok(Self {
+      chunk: Chunk { raw, owned: true },
+      samples,
+      frequency,
+      channels: channels as usize,
+    })
+  }
+
+  // 曲の始まりから position の位置から鳴らす
+  fn play_from(&self, position: Seconds) -> Result<(), PlayerError> {
+    let frame =
+      (position.as_f64().max(0.0) * self.frequency as f64) as usize;
+    let start = (frame * self.channels).min(self.samples.len());
+    BGM_CHANNEL.halt();
+    // 鳴らす範囲を samples の途中からに差し替える。止めている間だけ触る
+    unsafe {
+      (*self.chunk.raw).abuf =
+        self.samples[start..].as_ptr() as *mut u8;
+      (*self.chunk.raw).alen =
+        ((self.samples.len() - start) * 2) as u32;
+    }
+    BGM_CHANNEL.play(&self.chunk, 0).map_err(AudioError)?;
+    Ok(())
+  }
+}
+
 pub struct Player<'music> {
   music: Option<Music<'music>>,
+  stretched: Option<StretchedBgm>,
   chunks: Chunks,
 }
 
 impl<'music> Drop for Player<'music> {
   fn drop(&mut self) {
     sdl2::mixer::Music::halt();
+    BGM_CHANNEL.halt();
   }
 }
 
+#[allow(clippy::unnecessary_map_or)]
 fn load_chunks() -> Result<Chunks, PlayerError> {
   let path = Path::new("asset/");
   let mut chunks: Chunks = HashMap::new();
@@ -52,6 +125,7 @@ fn load_chunks() -> Result<Chunks, PlayerError> {
     }
   }
   Channel::all().set_volume(112); // the max is 128
+  sdl2::mixer::reserve_channels(1);
   Ok(chunks)
 }
 
@@ -59,19 +133,29 @@ impl<'music> Player<'music> {
   pub fn new() -> Self {
     Self {
       music: None,
+      stretched: None,
       chunks: load_chunks().expect("missing audio file dir"),
     }
   }
 
+  // speed が 1 でなければ、音の高さを変えずに速さを変えて流す
   pub fn change_bgm(
     &mut self,
     bgm_name: &str,
+    speed: f64,
   ) -> Result<(), PlayerError> {
     let bgm_file_path = format!("score/{}", bgm_name);
-    let music =
-      sdl2::mixer::Music::from_file(Path::new(&bgm_file_path))
+    let bgm_file_path = Path::new(&bgm_file_path);
+    if speed == 1.0 {
+      let music = sdl2::mixer::Music::from_file(bgm_file_path)
         .map_err(AudioError)?;
-    self.music = Some(music);
+      self.music = Some(music);
+      self.stretched = None;
+    } else {
+      self.stretched =
+        Some(StretchedBgm::load(bgm_file_path, speed)?);
+      self.music = None;
+    }
     self.play_bgm()?;
     Ok(())
   }
@@ -80,13 +164,43 @@ impl<'music> Player<'music> {
     if let Some(ref music) = self.music {
       music.play(0).map_err(AudioError)?;
     }
+    if let Some(ref stretched) = self.stretched {
+      stretched.play_from(0.0.into())?;
+    }
     Ok(())
   }
 
   pub fn stop_bgm(&self, fade_time: i32) -> Result<(), PlayerError> {
+    if self.stretched.is_some() {
+      BGM_CHANNEL.fade_out(fade_time);
+      return Ok(());
+    }
     sdl2::mixer::Music::fade_out(fade_time).map_err(AudioError)
   }
 
+  pub fn pause_bgm(&self) {
+    sdl2::mixer::Music::pause();
+    BGM_CHANNEL.pause();
+  }
+
+  pub fn resume_bgm(&self) {
+    sdl2::mixer::Music::resume();
+    BGM_CHANNEL.resume();
+  }
+
+  // 曲の始まりから position の位置に移る
+  // 形式によっては今の位置からの相対になるため、一度頭に戻してから移る
+  pub fn seek_bgm(
+    &self,
+    position: Seconds,
+  ) -> Result<(), PlayerError> {
+    if let Some(ref stretched) = self.stretched {
+      return stretched.play_from(position);
+    }
+    sdl2::mixer::Music::rewind();
+    sdl2::mixer::Music::set_pos(position.as_f64()).map_err(AudioError)
+  }
+
   fn play_se_file(&self, name: &str) -> Result<(), PlayerError> {
     let chunk = self.chunks.get(name).ok_or_else(|| {
       AudioError(format!("missing such audio file: {}", name))
@@ -0,0 +1,127 @@
+// 音の高さを変えずに再生の速さを変える (WSOLA)
+// 窓を半分ずつ重ねて足し合わせ、各窓は前の窓の続きに最も似ている位置から取る
+use std::f32::consts::PI;
+
+// 1 つの窓のフレーム数。44.1kHz で約 46ms
+const WINDOW: usize = 2048;
+const HOP: usize = WINDOW / 2;
+// 窓を取る位置を、本来の位置から前後にずらしてよいフレーム数
+const TOLERANCE: usize = 512;
+
+// channels 個ずつ交互に並んだ samples を、speed 倍の速さで鳴る長さに伸び縮みさせる
+pub fn time_stretch(
+  samples: &[i16],
+  channels: usize,
+  speed: f64,
+) -> Vec<i16> {
+  let frames = samples.len() / channels;
+  if speed == 1.0 || frames < WINDOW + TOLERANCE * 2 {
+    return samples.to_vec();
+  }
+  // 似ている位置を探すのには左右を混ぜた音を使う
+  let mono: Vec<f32> = samples
+    .chunks_exact(channels)
+    .map(|frame| {
+      frame.iter().map(|&s| s as f32).sum::<f32>() / channels as f32
+    })
+    .collect();
+  // 半分ずつ重ねると足して 1 になる窓
+  let window: Vec<f32> = (0..WINDOW)
+    .map(|i| 0.5 - 0.5 * (2.0 * PI * i as f32 / WINDOW as f32).cos())
+    .collect();
+
+  let out_frames = (frames as f64 / speed) as usize;
+  let mut out = vec![0.0f32; (out_frames + WINDOW) * channels];
+  let mut prev = 0;
+  for (index, out_pos) in (0..out_frames).step_by(HOP).enumerate() {
+    let ideal = (out_pos as f64 * speed) as usize;
+    let pos = if index == 0 {
+      0
+    } else {
+      most_similar(&mono, prev + HOP, ideal)
+    };
+    if frames < pos + WINDOW {
+      break;
+    }
+    for (i, weight) in window.iter().enumerate() {
+      for channel in 0..channels {
+        out[(out_pos + i) * channels + channel] +=
+          weight * samples[(pos + i) * channels + channel] as f32;
+      }
+    }
+    prev = pos;
+  }
+  out.truncate(out_frames * channels);
+  out
+    .iter()
+    .map(|&s| {
+      s.round().clamp(i16::MIN as f32, i16::MAX as f32) as i16
+    })
+    .collect()
+}
+
+// 重ねる HOP フレームの似ている度合い。計算を減らすため間引いて比べる
+fn similarity(mono: &[f32], a: usize, b: usize) -> f32 {
+  (0..HOP).step_by(4).map(|i| mono[a + i] * mono[b + i]).sum()
+}
+
+// ideal の前後から、natural から始まる音に最も似ている位置を探す
+fn most_similar(mono: &[f32], natural: usize, ideal: usize) -> usize {
+  let last = mono.len() - WINDOW;
+  let ideal = ideal.min(last);
+  if last < natural + HOP {
+    return ideal;
+  }
+  let best_in = |candidates: &mut dyn Iterator<Item = usize>| {
+    candidates
+      .map(|pos| (similarity(mono, natural, pos), pos))
+      .fold((f32::MIN, ideal), |best, candidate| {
+        if best.0 < candidate.0 {
+          candidate
+        } else {
+          best
+        }
+      })
+      .1
+  };
+  // 粗く探してから、その周りを細かく探す
+  let lower = ideal.saturating_sub(TOLERANCE);
+  let upper = (ideal + TOLERANCE).min(last);
+  let coarse = best_in(&mut (lower..=upper).step_by(8));
+  best_in(&mut (coarse.saturating_sub(7)..=(coarse + 7).min(last)))
+}
+
+#[test]
+fn keep_pitch() {
+  // 441Hz の正弦波。44.1kHz で 100 フレームごとに繰り返す
+  let sine: Vec<i16> = (0..44100)
+    .flat_map(|i| {
+      let s = (2.0 * PI * i as f32 / 100.0).sin() * 10000.0;
+      vec![s as i16, s as i16]
+    })
+    .collect();
+  let crossings = |samples: &[i16]| {
+    samples
+      .chunks_exact(2)
+      .map(|frame| frame[0])
+      .collect::<Vec<_>>()
+      .windows(2)
+      .filter(|pair| (pair[0] < 0) != (pair[1] < 0))
+      .count()
+  };
+  for &speed in &[0.5, 0.75, 1.5] {
+    let stretched = time_stretch(&sine, 2, speed);
+    let expected = (44100.0 / speed) as usize * 2;
+    assert_eq!(stretched.len(), expected);
+    // 端は窓が重ならないので、真ん中だけで周期を比べる
+    let middle = &stretched[expected / 4..expected * 3 / 4];
+    let per_frame =
+      crossings(middle) as f64 / (middle.len() / 2) as f64;
+    assert!(
+      (per_frame - 0.02).abs() < 0.001,
+      "speed {}: {}",
+      speed,
+      per_frame
+    );
+  }
+}
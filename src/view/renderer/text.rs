@@ -6,6 +6,7 @@ use sdl2::{
   ttf::Font,
 };
 
+#[allow(clippy::enum_variant_names)]
 #[derive(Debug)]
 pub enum TextError {
   FontError(sdl2::ttf::FontError),
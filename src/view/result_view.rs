@@ -1,15 +1,22 @@
 use super::{
   components::{
-    Button, ButtonProps, Header, HeaderProps, Stats, StatsProps,
+    Button, ButtonProps, Header, HeaderProps, KeyHeatmap,
+    KeyHeatmapProps, Stats, StatsProps,
   },
   handler::Handler,
   renderer::{Component, RenderCtx},
   View, ViewRoute,
 };
-use crate::model::exp::{
-  game_activity::GameScore, scoremap::MusicInfo,
+use crate::model::{
+  exp::{
+    game_activity::GameScore, note::NoteLog, scoremap::MusicInfo,
+  },
+  game::{KeyProfile, ScoringProfile},
+};
+use sdl2::{
+  pixels::Color,
+  rect::{Point, Rect},
 };
-use sdl2::{pixels::Color, rect::Rect};
 use std::{cell::RefCell, rc::Rc, time::Instant};
 
 pub struct ResultView<'ttf, 'canvas> {
@@ -17,6 +24,10 @@ pub struct ResultView<'ttf, 'canvas> {
   handler: Handler,
   score: GameScore,
   music_info: MusicInfo,
+  scoring: ScoringProfile,
+  note_logs: Vec<NoteLog>,
+  // 記録を保存できなかったときの知らせ
+  save_errors: Vec<String>,
 }
 
 impl<'ttf, 'canvas> ResultView<'ttf, 'canvas> {
@@ -25,13 +36,83 @@ impl<'ttf, 'canvas> ResultView<'ttf, 'canvas> {
     handler: Handler,
     score: GameScore,
     music_info: MusicInfo,
+    scoring: ScoringProfile,
+    note_logs: Vec<NoteLog>,
+    save_errors: Vec<String>,
   ) -> Self {
     Self {
       renderer,
       handler,
       score,
       music_info,
+      scoring,
+      note_logs,
+      save_errors,
+    }
+  }
+
+  // この遊びで苦手だったキーと読み仮名
+  fn weak_keys(&self, profile: &KeyProfile) -> Vec<String> {
+    // 数回しか打っていないものは偶然の間違いが目立つので外す
+    const MIN_COUNT: u32 = 3;
+    let keys: Vec<_> = profile
+      .weakest_keys(MIN_COUNT)
+      .into_iter()
+      .filter(|(_, record)| 0 < record.mistaken)
+      .take(3)
+      .map(|(key, _)| key.to_uppercase().to_string())
+      .collect();
+    let kana: Vec<_> = profile
+      .weakest_kana(MIN_COUNT)
+      .into_iter()
+      .filter(|(_, record)| 0 < record.mistaken)
+      .take(3)
+      .map(|(kana, _)| kana)
+      .collect();
+    let mut lines = vec![];
+    if !keys.is_empty() {
+      lines.push(format!("苦手なキー {}", keys.join(" ")));
+    }
+    if !kana.is_empty() {
+      lines.push(format!("苦手な仮名 {}", kana.join(" ")));
     }
+    lines
+  }
+
+  // 文ごとの記録をまとめた一行
+  fn note_summary(&self) -> Option<String> {
+    let reactions: Vec<_> = self
+      .note_logs
+      .iter()
+      .filter_map(|log| log.first_type)
+      .map(|time| time.as_f64())
+      .collect();
+    if reactions.is_empty() {
+      return None;
+    }
+    let average =
+      reactions.iter().sum::<f64>() / reactions.len() as f64;
+    let missed =
+      self.note_logs.iter().filter(|log| log.missed).count();
+    let mut mistakes = std::collections::BTreeMap::new();
+    for log in &self.note_logs {
+      for (key, count) in &log.mistakes {
+        *mistakes.entry(*key).or_insert(0) += count;
+      }
+    }
+    let mut summary = format!(
+      "打ち始めまで平均 {:.2} 秒 / 打ち逃し {} 文",
+      average, missed
+    );
+    if let Some((key, count)) =
+      mistakes.iter().max_by_key(|(_, count)| **count)
+    {
+      summary.push_str(&format!(
+        " / 最も間違えたキー {} ({} 回)",
+        key, count
+      ));
+    }
+    Some(summary)
   }
 }
 
@@ -56,6 +137,7 @@ impl<'ttf, 'canvas> View for ResultView<'ttf, 'canvas> {
       StatsProps {
         type_per_second: 0.0,
         score: self.score.clone(),
+        scoring: self.scoring.clone(),
       },
       stats_dim,
     );
@@ -69,6 +151,17 @@ impl<'ttf, 'canvas> View for ResultView<'ttf, 'canvas> {
       header_dim,
     );
 
+    let key_profile = KeyProfile::from_logs(&self.note_logs);
+    let weak_keys = self.weak_keys(&key_profile);
+    let heatmap_dim =
+      Rect::new(20, client.height() as i32 - 95, 300, 80);
+    let heatmap = KeyHeatmap::new(
+      KeyHeatmapProps {
+        profile: key_profile,
+      },
+      heatmap_dim,
+    );
+
     const WIDTH: u32 = 240;
     const HEIGHT: u32 = 80;
     const MARGIN: u32 = 20;
@@ -124,9 +217,69 @@ impl<'ttf, 'canvas> View for ResultView<'ttf, 'canvas> {
       stats.update(StatsProps {
         type_per_second: 0.0,
         score: self.score.clone(),
+        scoring: self.scoring.clone(),
       });
       stats.render(&mut self.renderer.borrow_mut())?;
 
+      // 等速でない遊びは、通常の結果と区別できるよう速さを添える
+      if self.score.speed != 1.0 {
+        let speed = self.score.speed;
+        self.renderer.borrow_mut().text(|style| {
+          style
+            .text(&format!("速度 {:.0}% での結果", speed * 100.0))
+            .color(Color::RGB(64, 79, 181))
+            .line_height(30)
+            .pos(stats_dim.top_left().offset(20, -110))
+        })?;
+      }
+
+      heatmap.render(&mut self.renderer.borrow_mut())?;
+      for (index, line) in weak_keys.iter().enumerate() {
+        self.renderer.borrow_mut().text(|style| {
+          style
+            .text(line)
+            .color(Color::RGB(10, 14, 10))
+            .line_height(20)
+            .pos(
+              heatmap_dim.top_right().offset(10, index as i32 * 25),
+            )
+        })?;
+      }
+
+      if let Some(summary) = self.note_summary() {
+        self.renderer.borrow_mut().text(|style| {
+          style
+            .text(&summary)
+            .color(Color::RGB(10, 14, 10))
+            .line_height(20)
+            .pos(stats_dim.top_left().offset(20, -140))
+        })?;
+      }
+
+      for (index, error) in self.save_errors.iter().enumerate() {
+        self.renderer.borrow_mut().text(|style| {
+          style
+            .text(error)
+            .color(Color::RGB(250, 119, 109))
+            .line_height(20)
+            .pos(Point::new(20, 10 + index as i32 * 25))
+        })?;
+      }
+
+      if self.score.failed {
+        let progress = self.score.progress;
+        self.renderer.borrow_mut().text(|style| {
+          style
+            .text(&format!(
+              "ライフ切れ ({:.0}% 地点で終了)",
+              progress * 100.0
+            ))
+            .color(Color::RGB(250, 119, 109))
+            .line_height(30)
+            .pos(stats_dim.top_left().offset(20, -80))
+        })?;
+      }
+
       {
         let new_props = ButtonProps {
           border_color: Color::RGB(10, 14, 10),